@@ -0,0 +1,137 @@
+//! Short local names for Drive folders and files.
+//!
+//! Aliases are kept in `$XDG_CONFIG_HOME/share_drive/aliases.json` (falling
+//! back to `~/.config/share_drive/aliases.json`) and are referenced as
+//! `@name` wherever a folder or file URL or ID is accepted.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::config_home;
+use crate::error::{DriveError, Result};
+use crate::url_parser::extract_id;
+
+/// JSON file mapping alias names to Drive IDs.
+#[derive(Debug, Clone)]
+pub struct AliasStore {
+    path: PathBuf,
+}
+
+impl AliasStore {
+    /// Create a store backed by the file at `path`, created on first save.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Default file: `$XDG_CONFIG_HOME/share_drive/aliases.json`,
+    /// falling back to `~/.config/share_drive/aliases.json`.
+    pub fn default_path() -> Option<PathBuf> {
+        Some(config_home()?.join("aliases.json"))
+    }
+
+    /// File the store reads and writes.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// All aliases, sorted by name. A missing file means no aliases.
+    pub fn load(&self) -> Result<BTreeMap<String, String>> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(DriveError::FileReadError {
+                path: self.path.display().to_string(),
+                source: e,
+            }),
+        }
+    }
+
+    /// Point `name` (with or without the leading `@`) at the ID in
+    /// `url_or_id`, replacing any previous target. Returns the stored ID.
+    pub fn add(&self, name: &str, url_or_id: &str) -> Result<String> {
+        let name = alias_name(name)?;
+        let id = extract_id(url_or_id)?;
+        let mut aliases = self.load()?;
+        aliases.insert(name.to_string(), id.clone());
+        self.save(&aliases)?;
+        Ok(id)
+    }
+
+    /// Remove an alias, returning the ID it pointed to.
+    pub fn remove(&self, name: &str) -> Result<String> {
+        let name = alias_name(name)?;
+        let mut aliases = self.load()?;
+        let id = aliases
+            .remove(name)
+            .ok_or_else(|| DriveError::AliasNotFound(name.to_string()))?;
+        self.save(&aliases)?;
+        Ok(id)
+    }
+
+    /// Resolve `@name` to the aliased ID; anything else is passed to
+    /// [`extract_id`].
+    pub fn resolve(&self, input: &str) -> Result<String> {
+        match input.trim().strip_prefix('@') {
+            Some(name) => self
+                .load()?
+                .remove(name)
+                .ok_or_else(|| DriveError::AliasNotFound(name.to_string())),
+            None => extract_id(input),
+        }
+    }
+
+    fn save(&self, aliases: &BTreeMap<String, String>) -> Result<()> {
+        let write_err = |e| DriveError::FileWriteError {
+            path: self.path.display().to_string(),
+            source: e,
+        };
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(write_err)?;
+        }
+        let content = serde_json::to_string_pretty(aliases)?;
+        fs::write(&self.path, content).map_err(write_err)
+    }
+}
+
+/// Validate an alias name, dropping a leading `@`.
+fn alias_name(name: &str) -> Result<&str> {
+    let name = name.strip_prefix('@').unwrap_or(name);
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if !valid {
+        return Err(DriveError::InvalidAlias(name.to_string()));
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alias_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AliasStore::new(dir.path().join("nested/aliases.json"));
+        assert!(store.load().unwrap().is_empty());
+
+        let id = store
+            .add("reports", "https://drive.google.com/drive/folders/1abcDEF_123")
+            .unwrap();
+        assert_eq!(id, "1abcDEF_123");
+        store.add("@raw", "1xyz").unwrap();
+
+        assert_eq!(store.resolve("@reports").unwrap(), "1abcDEF_123");
+        assert_eq!(store.resolve("1other").unwrap(), "1other");
+        assert!(matches!(store.resolve("@missing"), Err(DriveError::AliasNotFound(_))));
+
+        assert_eq!(store.remove("raw").unwrap(), "1xyz");
+        assert_eq!(store.load().unwrap().len(), 1);
+        assert!(matches!(store.remove("raw"), Err(DriveError::AliasNotFound(_))));
+
+        assert!(matches!(store.add("has space", "1a"), Err(DriveError::InvalidAlias(_))));
+        assert!(matches!(store.add("@", "1a"), Err(DriveError::InvalidAlias(_))));
+    }
+}
@@ -1,4 +1,4 @@
-//! Service account authentication for Google APIs.
+//! Service account and user account authentication for Google APIs.
 
 use std::fs;
 use std::path::Path;
@@ -11,13 +11,35 @@ use serde::Serialize;
 use tokio::sync::RwLock;
 
 use crate::error::{DriveError, Result};
-use crate::models::{ServiceAccountCredentials, TokenResponse};
+use crate::http_log;
+use crate::keyring;
+use crate::models::{AuthorizedUser, ServiceAccountCredentials, TokenResponse};
 
 /// Google OAuth2 token endpoint.
-const TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+pub(crate) const TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
 
-/// Google Drive API scope.
-const DRIVE_SCOPE: &str = "https://www.googleapis.com/auth/drive";
+/// OAuth scope requested for Drive access.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scope {
+    /// Full access to all files (`drive`).
+    #[default]
+    Full,
+    /// Read-only access to all files (`drive.readonly`).
+    ReadOnly,
+    /// Access only to files the tool created or was given (`drive.file`).
+    File,
+}
+
+impl Scope {
+    /// The scope URL sent to Google.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Full => "https://www.googleapis.com/auth/drive",
+            Scope::ReadOnly => "https://www.googleapis.com/auth/drive.readonly",
+            Scope::File => "https://www.googleapis.com/auth/drive.file",
+        }
+    }
+}
 
 /// JWT claims for service account authentication.
 #[derive(Debug, Serialize)]
@@ -27,6 +49,9 @@ struct Claims {
     aud: String,   // Audience (token endpoint)
     exp: u64,      // Expiration time
     iat: u64,      // Issued at
+    // User to impersonate (domain-wide delegation)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sub: Option<String>,
 }
 
 /// Cached access token with expiration.
@@ -36,32 +61,132 @@ struct CachedToken {
     expires_at: SystemTime,
 }
 
-/// Authenticator for Google APIs using service account credentials.
+/// Whose credentials an [`Authenticator`] uses.
+#[derive(Debug)]
+enum Credentials {
+    ServiceAccount(ServiceAccountCredentials),
+    /// A Google account signed in with [`crate::oauth`].
+    User(AuthorizedUser),
+}
+
+/// Authenticator for Google APIs using service account or user credentials.
 #[derive(Clone)]
 pub struct Authenticator {
-    credentials: Arc<ServiceAccountCredentials>,
+    credentials: Arc<Credentials>,
+    /// Workspace user a service account acts on behalf of.
+    subject: Option<String>,
+    scope: Scope,
+    /// Token endpoint overriding Google's and the one in the credentials.
+    token_uri: Option<String>,
     client: Client,
     cached_token: Arc<RwLock<Option<CachedToken>>>,
 }
 
 impl Authenticator {
-    /// Create a new authenticator from a service account JSON file.
+    /// Create a new authenticator from a service account JSON key, or from
+    /// the credentials saved by `auth login` (`"type": "authorized_user"`).
+    ///
+    /// A refresh token kept in the keyring is read from it here.
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = fs::read_to_string(path.as_ref())
             .map_err(DriveError::CredentialsFileError)?;
-        let credentials: ServiceAccountCredentials = serde_json::from_str(&content)?;
-        Ok(Self::new(credentials))
+        Self::from_json_str(&content)
     }
 
-    /// Create a new authenticator from credentials.
+    /// Like [`Self::from_file`], but for credentials already in memory, e.g.
+    /// from a CI secret in an environment variable.
+    pub fn from_json_str(json: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        if value["type"] == "authorized_user" {
+            let mut user: AuthorizedUser = serde_json::from_value(value)?;
+            if user.keyring {
+                user.refresh_token = keyring::load(user.keyring_account())?;
+            }
+            return Ok(Self::from_authorized_user(user));
+        }
+        Ok(Self::new(serde_json::from_value(value)?))
+    }
+
+    /// Create a new authenticator from service account credentials.
     pub fn new(credentials: ServiceAccountCredentials) -> Self {
+        Self::with_credentials(Credentials::ServiceAccount(credentials))
+    }
+
+    /// Create a new authenticator acting as a signed-in Google account.
+    pub fn from_authorized_user(user: AuthorizedUser) -> Self {
+        Self::with_credentials(Credentials::User(user))
+    }
+
+    fn with_credentials(credentials: Credentials) -> Self {
         Self {
             credentials: Arc::new(credentials),
+            subject: None,
+            scope: Scope::default(),
+            token_uri: None,
             client: Client::new(),
             cached_token: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// Email address of the service account or signed-in account.
+    ///
+    /// Falls back to the OAuth client ID for accounts whose address was
+    /// not recorded at sign-in.
+    pub fn client_email(&self) -> &str {
+        match *self.credentials {
+            Credentials::ServiceAccount(ref sa) => &sa.client_email,
+            Credentials::User(ref user) => user.account.as_deref().unwrap_or(&user.client_id),
+        }
+    }
+
+    /// Act on behalf of the Workspace user `email` through domain-wide
+    /// delegation, which an administrator must have granted to the service
+    /// account. Has no effect with user credentials.
+    pub fn with_subject<S: Into<String>>(mut self, email: S) -> Self {
+        self.subject = Some(email.into());
+        self.cached_token = Arc::new(RwLock::new(None));
+        self
+    }
+
+    /// Request `scope` instead of full Drive access.
+    ///
+    /// Only affects service accounts: the scope of user credentials is
+    /// fixed when signing in.
+    pub fn with_scope(mut self, scope: Scope) -> Self {
+        self.scope = scope;
+        self.cached_token = Arc::new(RwLock::new(None));
+        self
+    }
+
+    /// The scope requested for service account tokens.
+    pub fn scope(&self) -> Scope {
+        self.scope
+    }
+
+    /// Send token requests with `client`, e.g. one configured with a proxy.
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Request tokens from `token_uri` instead of Google's token endpoint,
+    /// e.g. through an API gateway or from a mock server.
+    pub fn with_token_uri<S: Into<String>>(mut self, token_uri: S) -> Self {
+        self.token_uri = Some(token_uri.into());
+        self.cached_token = Arc::new(RwLock::new(None));
+        self
+    }
+
+    /// The impersonated user, if any.
+    pub fn subject(&self) -> Option<&str> {
+        self.subject.as_deref()
+    }
+
+    /// Whether this authenticates as a service account rather than a user.
+    pub fn is_service_account(&self) -> bool {
+        matches!(*self.credentials, Credentials::ServiceAccount(_))
+    }
+
     /// Get a valid access token, refreshing if necessary.
     pub async fn get_access_token(&self) -> Result<String> {
         // Check if we have a valid cached token
@@ -88,42 +213,45 @@ impl Authenticator {
         Ok(new_token.access_token)
     }
 
-    /// Refresh the access token using JWT assertion.
-    async fn refresh_token(&self) -> Result<CachedToken> {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_secs();
+    /// Drop the cached access token so the next call fetches a new one.
+    pub async fn invalidate(&self) {
+        let mut cached = self.cached_token.write().await;
+        *cached = None;
+        tracing::debug!("dropped cached access token");
+    }
 
-        let claims = Claims {
-            iss: self.credentials.client_email.clone(),
-            scope: DRIVE_SCOPE.to_string(),
-            aud: TOKEN_URI.to_string(),
-            iat: now,
-            exp: now + 3600, // 1 hour
+    /// Obtain a new access token.
+    async fn refresh_token(&self) -> Result<CachedToken> {
+        let (token_uri, params) = match *self.credentials {
+            Credentials::ServiceAccount(ref sa) => {
+                (TOKEN_URI, jwt_grant(sa, self.scope, self.subject.as_deref())?)
+            }
+            Credentials::User(ref user) => (
+                user.token_uri.as_deref().unwrap_or(TOKEN_URI),
+                vec![
+                    ("grant_type", "refresh_token".to_string()),
+                    ("client_id", user.client_id.clone()),
+                    ("client_secret", user.client_secret.clone()),
+                    ("refresh_token", user.refresh_token.clone()),
+                ],
+            ),
         };
 
-        // Create JWT
-        let header = Header::new(Algorithm::RS256);
-        let key = EncodingKey::from_rsa_pem(self.credentials.private_key.as_bytes())?;
-        let jwt = encode(&header, &claims, &key)?;
-
-        // Exchange JWT for access token
-        let params = [
-            ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
-            ("assertion", &jwt),
-        ];
-
-        let response = self
-            .client
-            .post(TOKEN_URI)
-            .form(&params)
-            .send()
-            .await?;
+        let token_uri = self.token_uri.as_deref().unwrap_or(token_uri);
+        tracing::debug!(
+            account = self.client_email(),
+            subject = self.subject(),
+            scope = self.scope.as_str(),
+            token_uri,
+            "requesting access token"
+        );
+        let request = self.client.post(token_uri).form(&params).build()?;
+        let response = http_log::execute(&self.client, request).await?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
+            tracing::warn!(status = status.as_u16(), "access token request failed");
             return Err(DriveError::TokenRefreshError(format!(
                 "Status {}: {}",
                 status, body
@@ -131,6 +259,7 @@ impl Authenticator {
         }
 
         let token_response: TokenResponse = response.json().await?;
+        tracing::debug!(expires_in = token_response.expires_in, "access token issued");
 
         let expires_at =
             SystemTime::now() + Duration::from_secs(token_response.expires_in);
@@ -142,6 +271,38 @@ impl Authenticator {
     }
 }
 
+/// Token request parameters exchanging a signed JWT for an access token,
+/// optionally for a token acting as the user `subject`.
+fn jwt_grant(
+    credentials: &ServiceAccountCredentials,
+    scope: Scope,
+    subject: Option<&str>,
+) -> Result<Vec<(&'static str, String)>> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs();
+
+    let claims = Claims {
+        iss: credentials.client_email.clone(),
+        sub: subject.map(str::to_string),
+        scope: scope.as_str().to_string(),
+        aud: TOKEN_URI.to_string(),
+        iat: now,
+        exp: now + 3600, // 1 hour
+    };
+
+    // Create JWT
+    let header = Header::new(Algorithm::RS256);
+    let key = EncodingKey::from_rsa_pem(credentials.private_key.as_bytes())?;
+    let jwt = encode(&header, &claims, &key)?;
+
+    Ok(vec![
+        ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer".to_string()),
+        ("assertion", jwt),
+    ])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -150,7 +311,8 @@ mod tests {
     fn test_claims_serialization() {
         let claims = Claims {
             iss: "test@example.iam.gserviceaccount.com".to_string(),
-            scope: DRIVE_SCOPE.to_string(),
+            sub: None,
+            scope: Scope::ReadOnly.as_str().to_string(),
             aud: TOKEN_URI.to_string(),
             iat: 1234567890,
             exp: 1234571490,
@@ -158,6 +320,65 @@ mod tests {
 
         let json = serde_json::to_string(&claims).unwrap();
         assert!(json.contains("test@example.iam.gserviceaccount.com"));
-        assert!(json.contains(DRIVE_SCOPE));
+        assert!(json.contains("/auth/drive.readonly"));
+        assert!(!json.contains("sub"));
+
+        let impersonating = Claims {
+            sub: Some("user@corp.com".to_string()),
+            ..claims
+        };
+        let json = serde_json::to_string(&impersonating).unwrap();
+        assert!(json.contains(r#""sub":"user@corp.com""#));
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_clears_cached_token() {
+        let auth = Authenticator::new(ServiceAccountCredentials {
+            client_email: "test@example.iam.gserviceaccount.com".to_string(),
+            private_key: "key".to_string(),
+            token_uri: None,
+        });
+        *auth.cached_token.write().await = Some(CachedToken {
+            access_token: "cached".to_string(),
+            expires_at: SystemTime::now() + Duration::from_secs(3600),
+        });
+
+        assert_eq!(auth.get_access_token().await.unwrap(), "cached");
+
+        auth.invalidate().await;
+        assert!(auth.cached_token.read().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_authorized_user_refresh() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/token")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("grant_type".into(), "refresh_token".into()),
+                mockito::Matcher::UrlEncoded("refresh_token".into(), "1//refresh".into()),
+            ]))
+            .with_body(
+                r#"{"access_token": "ya29.user", "token_type": "Bearer", "expires_in": 3599}"#,
+            )
+            .create_async()
+            .await;
+
+        let json = serde_json::json!({
+            "type": "authorized_user",
+            "client_id": "123.apps.googleusercontent.com",
+            "client_secret": "secret",
+            "refresh_token": "1//refresh",
+            "token_uri": format!("{}/token", server.url()),
+            "account": "me@example.com",
+        });
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), json.to_string()).unwrap();
+
+        let auth = Authenticator::from_file(file.path()).unwrap();
+        assert!(!auth.is_service_account());
+        assert_eq!(auth.client_email(), "me@example.com");
+        assert_eq!(auth.get_access_token().await.unwrap(), "ya29.user");
+        mock.assert_async().await;
     }
 }
@@ -0,0 +1,178 @@
+//! Drive batch requests: up to [`MAX_BATCH_SIZE`] API calls sent as the
+//! parts of one `multipart/mixed` HTTP request.
+//!
+//! The outer request carries the authorization and is answered with a
+//! `multipart/mixed` body holding one HTTP response per call.
+
+use crate::error::{DriveError, Result};
+
+/// Most calls Drive accepts in one batch request.
+pub const MAX_BATCH_SIZE: usize = 100;
+
+/// One call inside a batch, by method and path below the host,
+/// e.g. `DELETE /drive/v3/files/1AbC?supportsAllDrives=true`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BatchCall {
+    pub method: &'static str,
+    pub path: String,
+}
+
+/// The response to one [`BatchCall`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BatchResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Split an API base URL such as `https://www.googleapis.com/drive/v3` into
+/// its batch endpoint (`https://www.googleapis.com/batch/drive/v3`) and the
+/// path calls have inside a batch (`/drive/v3`).
+pub(crate) fn batch_urls(api_base: &str) -> (String, String) {
+    let host_start = api_base.find("://").map_or(0, |i| i + 3);
+    let path_start = api_base[host_start..]
+        .find('/')
+        .map_or(api_base.len(), |i| host_start + i);
+    let (origin, path) = api_base.split_at(path_start);
+    (format!("{}/batch{}", origin, path), path.to_string())
+}
+
+/// Body of a batch request with `calls` as its parts, numbered from 1.
+pub(crate) fn encode(boundary: &str, calls: &[BatchCall]) -> String {
+    let mut body = String::new();
+    for (i, call) in calls.iter().enumerate() {
+        body.push_str(&format!(
+            "--{}\r\nContent-Type: application/http\r\nContent-ID: <item{}>\r\n\r\n{} {}\r\n\r\n",
+            boundary,
+            i + 1,
+            call.method,
+            call.path
+        ));
+    }
+    body.push_str(&format!("--{}--\r\n", boundary));
+    body
+}
+
+/// Responses in a batch response `body`, in the order of the calls.
+///
+/// `content_type` is the response's `Content-Type` header, which names the
+/// boundary. Fails unless there is exactly one response for each of `count`
+/// calls.
+pub(crate) fn decode(content_type: &str, body: &str, count: usize) -> Result<Vec<BatchResponse>> {
+    let invalid = |message: &str| DriveError::ApiError {
+        status: 200,
+        message: format!("invalid batch response: {}", message),
+    };
+    let boundary = content_type
+        .split(';')
+        .find_map(|param| param.trim().strip_prefix("boundary="))
+        .map(|b| b.trim_matches('"'))
+        .ok_or_else(|| invalid("no boundary"))?;
+    let delimiter = format!("--{}", boundary);
+
+    let mut responses: Vec<Option<BatchResponse>> = vec![None; count];
+    let mut next = 0;
+    for part in body.split(delimiter.as_str()).skip(1) {
+        if part.starts_with("--") {
+            break;
+        }
+        let (headers, http) = split_head(part).ok_or_else(|| invalid("part without body"))?;
+        // Content-ID is `<response-item{n}>`; fall back to the part order
+        let index = headers
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-id"))
+            .and_then(|(_, value)| value.trim().strip_prefix("<response-item"))
+            .and_then(|rest| rest.strip_suffix('>'))
+            .and_then(|n| n.parse::<usize>().ok())
+            .and_then(|n| n.checked_sub(1))
+            .unwrap_or(next);
+        next = index + 1;
+
+        let (head, content) = split_head(http).unwrap_or((http, ""));
+        let status = head
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|code| code.parse().ok())
+            .ok_or_else(|| invalid("part without status line"))?;
+        let slot = responses
+            .get_mut(index)
+            .ok_or_else(|| invalid("unexpected response"))?;
+        *slot = Some(BatchResponse {
+            status,
+            body: content.trim_end().to_string(),
+        });
+    }
+    responses
+        .into_iter()
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| invalid("missing responses"))
+}
+
+/// Split a header block from what follows the first blank line.
+fn split_head(text: &str) -> Option<(&str, &str)> {
+    let text = text.trim_start_matches(['\r', '\n']);
+    text.split_once("\r\n\r\n").or_else(|| text.split_once("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_urls() {
+        let (url, path) = batch_urls("https://www.googleapis.com/drive/v3");
+        assert_eq!(url, "https://www.googleapis.com/batch/drive/v3");
+        assert_eq!(path, "/drive/v3");
+
+        let (url, path) = batch_urls("http://127.0.0.1:1234/drive/v3");
+        assert_eq!(url, "http://127.0.0.1:1234/batch/drive/v3");
+        assert_eq!(path, "/drive/v3");
+    }
+
+    #[test]
+    fn test_encode() {
+        let calls = [
+            BatchCall {
+                method: "DELETE",
+                path: "/drive/v3/files/a".to_string(),
+            },
+            BatchCall {
+                method: "DELETE",
+                path: "/drive/v3/files/b".to_string(),
+            },
+        ];
+        let body = encode("xyz", &calls);
+        assert_eq!(
+            body,
+            "--xyz\r\nContent-Type: application/http\r\nContent-ID: <item1>\r\n\r\n\
+             DELETE /drive/v3/files/a\r\n\r\n\
+             --xyz\r\nContent-Type: application/http\r\nContent-ID: <item2>\r\n\r\n\
+             DELETE /drive/v3/files/b\r\n\r\n\
+             --xyz--\r\n"
+        );
+    }
+
+    #[test]
+    fn test_decode() {
+        let body = "--resp\r\nContent-Type: application/http\r\nContent-ID: <response-item2>\r\n\r\n\
+                    HTTP/1.1 404 Not Found\r\nContent-Type: application/json\r\n\r\n\
+                    {\"error\": {\"code\": 404, \"message\": \"File not found\"}}\r\n\
+                    --resp\r\nContent-Type: application/http\r\nContent-ID: <response-item1>\r\n\r\n\
+                    HTTP/1.1 204 No Content\r\n\r\n\r\n\
+                    --resp--\r\n";
+        let responses = decode("multipart/mixed; boundary=resp", body, 2).unwrap();
+        assert_eq!(responses[0].status, 204);
+        assert_eq!(responses[0].body, "");
+        assert_eq!(responses[1].status, 404);
+        assert!(responses[1].body.contains("File not found"));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert!(decode("multipart/mixed", "", 1).is_err());
+        let body = "--resp\r\nContent-Type: application/http\r\n\r\nHTTP/1.1 204 No Content\r\n\r\n--resp--";
+        assert!(decode("multipart/mixed; boundary=resp", body, 2).is_err());
+        assert_eq!(decode("multipart/mixed; boundary=\"resp\"", body, 1).unwrap()[0].status, 204);
+    }
+}
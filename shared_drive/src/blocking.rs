@@ -0,0 +1,218 @@
+//! A synchronous client for callers that are not async, available with the
+//! `blocking` feature.
+//!
+//! [`SharedDriveClient`] wraps the async [`crate::SharedDriveClient`] and
+//! runs each call to completion on an internal single-threaded runtime. It
+//! must not be used from within an async runtime; there, use the async
+//! client directly.
+//!
+//! ```no_run
+//! use share_drive::blocking::SharedDriveClient;
+//! use share_drive::Authenticator;
+//!
+//! fn main() -> share_drive::Result<()> {
+//!     let auth = Authenticator::from_file("service-account.json")?;
+//!     let client = SharedDriveClient::new(auth, "drive-id".to_string())?;
+//!     for file in client.list_files("folder-id")? {
+//!         println!("{}", file);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::path::Path;
+
+use tokio::runtime::{Builder, Runtime};
+
+use crate::auth::Authenticator;
+use crate::client::{DownloadOptions, ListOptions, UploadOptions, UploadOutcome};
+use crate::error::{DriveError, Result};
+use crate::models::{About, FileMetadata};
+
+/// Blocking counterpart of [`crate::SharedDriveClient`].
+pub struct SharedDriveClient {
+    inner: crate::SharedDriveClient,
+    runtime: Runtime,
+}
+
+impl SharedDriveClient {
+    /// Create a client for a Shared Drive, see [`crate::SharedDriveClient::new`].
+    pub fn new(auth: Authenticator, drive_id: String) -> Result<Self> {
+        Self::from_async(crate::SharedDriveClient::new(auth, drive_id))
+    }
+
+    /// Create a client for the authenticated account's My Drive.
+    pub fn my_drive(auth: Authenticator) -> Result<Self> {
+        Self::from_async(crate::SharedDriveClient::my_drive(auth))
+    }
+
+    /// Wrap an async client, e.g. one configured with its `with_*` methods.
+    pub fn from_async(inner: crate::SharedDriveClient) -> Result<Self> {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(DriveError::RuntimeError)?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// The wrapped async client.
+    pub fn inner(&self) -> &crate::SharedDriveClient {
+        &self.inner
+    }
+
+    /// See [`crate::SharedDriveClient::list_files`].
+    pub fn list_files(&self, parent_id: &str) -> Result<Vec<FileMetadata>> {
+        self.runtime.block_on(self.inner.list_files(parent_id))
+    }
+
+    /// See [`crate::SharedDriveClient::list_files_with_options`].
+    pub fn list_files_with_options(
+        &self,
+        parent_id: &str,
+        options: &ListOptions,
+    ) -> Result<Vec<FileMetadata>> {
+        self.runtime
+            .block_on(self.inner.list_files_with_options(parent_id, options))
+    }
+
+    /// See [`crate::SharedDriveClient::list_files_recursive`].
+    pub fn list_files_recursive(&self, folder_id: &str) -> Result<Vec<(String, FileMetadata)>> {
+        self.runtime
+            .block_on(self.inner.list_files_recursive(folder_id))
+    }
+
+    /// See [`crate::SharedDriveClient::query_files`].
+    pub fn query_files(&self, query: &str) -> Result<Vec<FileMetadata>> {
+        self.runtime.block_on(self.inner.query_files(query))
+    }
+
+    /// See [`crate::SharedDriveClient::search`].
+    pub fn search(&self, text: &str, options: &ListOptions) -> Result<Vec<FileMetadata>> {
+        self.runtime.block_on(self.inner.search(text, options))
+    }
+
+    /// See [`crate::SharedDriveClient::find_file`].
+    pub fn find_file(&self, name: &str, parent_id: &str) -> Result<Option<FileMetadata>> {
+        self.runtime.block_on(self.inner.find_file(name, parent_id))
+    }
+
+    /// See [`crate::SharedDriveClient::resolve_path`].
+    pub fn resolve_path(&self, components: &[String]) -> Result<FileMetadata> {
+        self.runtime.block_on(self.inner.resolve_path(components))
+    }
+
+    /// See [`crate::SharedDriveClient::get_file`].
+    pub fn get_file(&self, file_id: &str) -> Result<FileMetadata> {
+        self.runtime.block_on(self.inner.get_file(file_id))
+    }
+
+    /// See [`crate::SharedDriveClient::generate_ids`].
+    pub fn generate_ids(&self, count: usize) -> Result<Vec<String>> {
+        self.runtime.block_on(self.inner.generate_ids(count))
+    }
+
+    /// See [`crate::SharedDriveClient::get_files`].
+    pub fn get_files<S: AsRef<str>>(&self, file_ids: &[S]) -> Result<Vec<Result<FileMetadata>>> {
+        self.runtime.block_on(self.inner.get_files(file_ids))
+    }
+
+    /// See [`crate::SharedDriveClient::delete_file`].
+    pub fn delete_file(&self, file_id: &str) -> Result<()> {
+        self.runtime.block_on(self.inner.delete_file(file_id))
+    }
+
+    /// See [`crate::SharedDriveClient::delete_files`].
+    pub fn delete_files<S: AsRef<str>>(&self, file_ids: &[S]) -> Vec<Result<()>> {
+        self.runtime.block_on(self.inner.delete_files(file_ids))
+    }
+
+    /// See [`crate::SharedDriveClient::about`].
+    pub fn about(&self) -> Result<About> {
+        self.runtime.block_on(self.inner.about())
+    }
+
+    /// See [`crate::SharedDriveClient::upload_file`].
+    pub fn upload_file<P: AsRef<Path>>(
+        &self,
+        local_path: P,
+        parent_id: &str,
+    ) -> Result<FileMetadata> {
+        self.runtime
+            .block_on(self.inner.upload_file(local_path, parent_id))
+    }
+
+    /// See [`crate::SharedDriveClient::upload_file_with_options`]; progress is
+    /// not reported.
+    pub fn upload_file_with_options<P: AsRef<Path>>(
+        &self,
+        local_path: P,
+        parent_id: &str,
+        options: &UploadOptions,
+    ) -> Result<UploadOutcome> {
+        self.runtime.block_on(
+            self.inner
+                .upload_file_with_options(local_path, parent_id, options, None),
+        )
+    }
+
+    /// See [`crate::SharedDriveClient::download_file`].
+    pub fn download_file<P: AsRef<Path>>(
+        &self,
+        file_id: &str,
+        destination: P,
+    ) -> Result<FileMetadata> {
+        self.runtime
+            .block_on(self.inner.download_file(file_id, destination))
+    }
+
+    /// See [`crate::SharedDriveClient::download_file_with_options`]; progress
+    /// is not reported.
+    pub fn download_file_with_options<P: AsRef<Path>>(
+        &self,
+        file_id: &str,
+        destination: P,
+        options: &DownloadOptions,
+    ) -> Result<FileMetadata> {
+        self.runtime.block_on(self.inner.download_file_with_options(
+            file_id,
+            destination,
+            options,
+            None,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocking_list_files() {
+        let mut server = mockito::Server::new();
+        server
+            .mock("POST", "/token")
+            .with_body(r#"{"access_token": "ya29.t", "token_type": "Bearer", "expires_in": 3600}"#)
+            .create();
+        let files = server
+            .mock("GET", "/drive/v3/files")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"files": [{"id": "f1", "name": "a.txt"}]}"#)
+            .create();
+
+        let auth = Authenticator::from_json_str(
+            r#"{"type": "authorized_user", "client_id": "id", "client_secret": "s",
+                "refresh_token": "1//r"}"#,
+        )
+        .unwrap()
+        .with_token_uri(format!("{}/token", server.url()));
+        let inner = crate::SharedDriveClient::my_drive(auth).with_base_urls(
+            format!("{}/drive/v3", server.url()),
+            format!("{}/upload/drive/v3", server.url()),
+        );
+        let client = SharedDriveClient::from_async(inner).unwrap();
+
+        let listed = client.list_files("folder1").unwrap();
+        assert_eq!(listed[0].name, "a.txt");
+        files.assert();
+    }
+}
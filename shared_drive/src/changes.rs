@@ -0,0 +1,104 @@
+//! Persistent page tokens for the Drive changes feed.
+//!
+//! The changes API reports everything that happened after a page token was
+//! issued. Saving the newest token after each pull means the next run only
+//! sees what changed in between.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DriveError, Result};
+use crate::session::{state_home, unix_now};
+
+/// Saved position in the changes feed of one drive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedPageToken {
+    /// Shared Drive the token belongs to, `None` for My Drive.
+    pub drive_id: Option<String>,
+    /// Token to pass to the next `changes.list` call.
+    pub page_token: String,
+    /// When the token was saved (seconds since the Unix epoch).
+    pub updated_at: u64,
+}
+
+/// Directory of [`SavedPageToken`]s, one JSON file per drive.
+#[derive(Debug, Clone)]
+pub struct PageTokenStore {
+    dir: PathBuf,
+}
+
+impl PageTokenStore {
+    /// Create a store rooted at `dir`. The directory is created on first save.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Default directory: `$XDG_STATE_HOME/share_drive/changes`,
+    /// falling back to `~/.local/state/share_drive/changes`.
+    pub fn default_dir() -> Option<PathBuf> {
+        Some(state_home()?.join("changes"))
+    }
+
+    /// Directory the store writes to.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Load the saved token for a drive (`None` for My Drive), if any.
+    ///
+    /// Unreadable or corrupt token files are treated as absent.
+    pub fn load(&self, drive_id: Option<&str>) -> Option<SavedPageToken> {
+        let content = fs::read_to_string(self.token_path(drive_id)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Save (or replace) the token for a drive.
+    pub fn save(&self, drive_id: Option<&str>, page_token: &str) -> Result<SavedPageToken> {
+        let saved = SavedPageToken {
+            drive_id: drive_id.map(str::to_string),
+            page_token: page_token.to_string(),
+            updated_at: unix_now(),
+        };
+        let path = self.token_path(drive_id);
+        let write_err = |e| DriveError::FileWriteError {
+            path: path.display().to_string(),
+            source: e,
+        };
+
+        fs::create_dir_all(&self.dir).map_err(write_err)?;
+        let content = serde_json::to_string_pretty(&saved)?;
+        fs::write(&path, content).map_err(write_err)?;
+        Ok(saved)
+    }
+
+    fn token_path(&self, drive_id: Option<&str>) -> PathBuf {
+        let key = md5::compute(drive_id.unwrap_or("my-drive"));
+        self.dir.join(format!("{:x}.json", key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_load_per_drive() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = PageTokenStore::new(dir.path().join("changes"));
+
+        assert!(store.load(Some("drive1")).is_none());
+
+        store.save(Some("drive1"), "100").unwrap();
+        store.save(None, "7").unwrap();
+        assert_eq!(store.load(Some("drive1")).unwrap().page_token, "100");
+        assert_eq!(store.load(None).unwrap().page_token, "7");
+        assert!(store.load(Some("drive2")).is_none());
+
+        store.save(Some("drive1"), "142").unwrap();
+        let saved = store.load(Some("drive1")).unwrap();
+        assert_eq!(saved.page_token, "142");
+        assert_eq!(saved.drive_id.as_deref(), Some("drive1"));
+    }
+}
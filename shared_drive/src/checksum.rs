@@ -0,0 +1,163 @@
+//! Local file checksums for comparing against Drive's `md5Checksum`.
+
+use std::path::Path;
+
+use tokio::fs::File;
+use tokio::io::AsyncReadExt;
+
+use crate::error::{DriveError, Result};
+
+/// Buffer size used when hashing files (1 MB).
+const HASH_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Compute the MD5 checksum of a local file as a lowercase hex string.
+///
+/// The file is streamed in chunks, so large files are not read into memory.
+pub async fn md5_file<P: AsRef<Path>>(path: P) -> Result<String> {
+    let path = path.as_ref();
+    let path_str = path.display().to_string();
+    let mut file = File::open(path).await.map_err(|e| DriveError::FileReadError {
+        path: path_str.clone(),
+        source: e,
+    })?;
+
+    let mut context = md5::Context::new();
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+
+    loop {
+        let bytes_read = file.read(&mut buffer).await.map_err(|e| DriveError::FileReadError {
+            path: path_str.clone(),
+            source: e,
+        })?;
+        if bytes_read == 0 {
+            break;
+        }
+        context.consume(&buffer[..bytes_read]);
+    }
+
+    Ok(format!("{:x}", context.compute()))
+}
+
+/// Check that a local file's MD5 checksum equals `expected`.
+///
+/// Returns [`DriveError::ChecksumMismatch`] when the digests differ.
+pub async fn verify_md5<P: AsRef<Path>>(path: P, expected: &str) -> Result<()> {
+    let path = path.as_ref();
+    let actual = md5_file(path).await?;
+    if actual.eq_ignore_ascii_case(expected) {
+        Ok(())
+    } else {
+        Err(DriveError::ChecksumMismatch {
+            path: path.display().to_string(),
+            expected: expected.to_string(),
+            actual,
+        })
+    }
+}
+
+/// Check the checksum Drive reports for an uploaded file against the
+/// `local` one computed while sending it.
+///
+/// Files Drive keeps no checksum for, such as converted Google Docs, pass.
+pub(crate) fn verify_uploaded(path: &str, local: &str, remote: Option<&str>) -> Result<()> {
+    match remote {
+        Some(remote) if !remote.eq_ignore_ascii_case(local) => Err(DriveError::ChecksumMismatch {
+            path: path.to_string(),
+            expected: local.to_string(),
+            actual: remote.to_string(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// MD5 of a file hashed chunk by chunk as it is uploaded.
+///
+/// Chunks sent again after a failure are only hashed once, so the digest
+/// stays that of the file as long as every byte passes through in order.
+pub(crate) struct ChunkHasher {
+    context: md5::Context,
+    hashed: u64,
+}
+
+impl ChunkHasher {
+    pub(crate) fn new() -> Self {
+        Self {
+            context: md5::Context::new(),
+            hashed: 0,
+        }
+    }
+
+    /// Hash the part of `data`, read at `offset` in the file, not yet seen.
+    /// Data past a gap is ignored, leaving the digest incomplete.
+    pub(crate) fn update(&mut self, offset: u64, data: &[u8]) {
+        let end = offset + data.len() as u64;
+        if offset <= self.hashed && self.hashed < end {
+            let skip = (self.hashed - offset) as usize;
+            self.context.consume(&data[skip..]);
+            self.hashed = end;
+        }
+    }
+
+    /// The digest as a lowercase hex string, or `None` unless exactly the
+    /// first `size` bytes were hashed, e.g. when a saved session was resumed.
+    pub(crate) fn finish(self, size: u64) -> Option<String> {
+        (self.hashed == size).then(|| format!("{:x}", self.context.compute()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[tokio::test]
+    async fn test_md5_file() {
+        let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+        temp_file.write_all(b"hello world").unwrap();
+
+        let digest = md5_file(temp_file.path()).await.unwrap();
+        assert_eq!(digest, "5eb63bbbe01eeed093cb22bb8f5acdc3");
+    }
+
+    #[tokio::test]
+    async fn test_verify_md5() {
+        let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+        temp_file.write_all(b"hello world").unwrap();
+
+        assert!(verify_md5(temp_file.path(), "5eb63bbbe01eeed093cb22bb8f5acdc3").await.is_ok());
+        assert!(matches!(
+            verify_md5(temp_file.path(), "d41d8cd98f00b204e9800998ecf8427e").await,
+            Err(DriveError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_chunk_hasher() {
+        let mut hasher = ChunkHasher::new();
+        hasher.update(0, b"hello ");
+        // Resent from the middle of the first chunk
+        hasher.update(3, b"lo wor");
+        hasher.update(8, b"rld");
+        assert_eq!(hasher.finish(11).as_deref(), Some("5eb63bbbe01eeed093cb22bb8f5acdc3"));
+
+        let mut resumed = ChunkHasher::new();
+        resumed.update(6, b"world");
+        assert_eq!(resumed.finish(11), None);
+    }
+
+    #[test]
+    fn test_verify_uploaded() {
+        let local = "5eb63bbbe01eeed093cb22bb8f5acdc3";
+        assert!(verify_uploaded("a.txt", local, Some(local)).is_ok());
+        assert!(verify_uploaded("a.txt", local, None).is_ok());
+        assert!(matches!(
+            verify_uploaded("a.txt", local, Some("d41d8cd98f00b204e9800998ecf8427e")),
+            Err(DriveError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_md5_file_missing() {
+        assert!(md5_file("/nonexistent/path/file.bin").await.is_err());
+    }
+}
@@ -0,0 +1,221 @@
+//! Detection of new and changed files in a local directory.
+//!
+//! The directory is rescanned on every [`DirWatcher::poll`]; a file is
+//! reported once its size and modification time have stayed the same for the
+//! debounce period, so files still being written are not picked up half-way.
+//! With [`DirWatcher::watch`], file system notifications tell
+//! [`DirWatcher::changed`] when a rescan is worth doing.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use glob::Pattern;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+use crate::error::{DriveError, Result};
+
+/// Size and modification time of a file at one scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Snapshot {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+/// Watches the regular files directly inside a directory.
+///
+/// Hidden files (names starting with `.`) and files whose name matches one
+/// of the ignore patterns are never reported.
+#[derive(Debug)]
+pub struct DirWatcher {
+    root: PathBuf,
+    debounce: Duration,
+    ignore: Vec<Pattern>,
+    /// Files as they were when last reported (or primed).
+    reported: HashMap<PathBuf, Snapshot>,
+    /// Files that changed since they were reported, with when they were last seen changing.
+    pending: HashMap<PathBuf, (Snapshot, Instant)>,
+    /// Notifications of changes in `root`, once [`Self::watch`] was called.
+    events: Option<(RecommendedWatcher, mpsc::UnboundedReceiver<()>)>,
+}
+
+impl DirWatcher {
+    /// Create a watcher for `root`.
+    ///
+    /// # Arguments
+    /// * `debounce` - How long a file must stay unchanged before it is reported
+    /// * `ignore` - Glob patterns matched against file names
+    pub fn new<P: AsRef<Path>>(root: P, debounce: Duration, ignore: Vec<Pattern>) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            debounce,
+            ignore,
+            reported: HashMap::new(),
+            pending: HashMap::new(),
+            events: None,
+        }
+    }
+
+    /// Subscribe to file system notifications for the directory, so that
+    /// [`Self::changed`] returns as soon as something in it changes.
+    pub fn watch(&mut self) -> Result<()> {
+        let watch_error = |e: notify::Error| DriveError::WatchError {
+            path: self.root.display().to_string(),
+            message: e.to_string(),
+        };
+        let (tx, rx) = mpsc::unbounded_channel();
+        // Errors wake the caller too; the rescan then finds what changed
+        let mut watcher = notify::recommended_watcher(move |_: notify::Result<notify::Event>| {
+            let _ = tx.send(());
+        })
+        .map_err(watch_error)?;
+        watcher.watch(&self.root, RecursiveMode::NonRecursive).map_err(watch_error)?;
+        self.events = Some((watcher, rx));
+        Ok(())
+    }
+
+    /// Wait until the directory is reported to have changed, or at most
+    /// `timeout`. Without [`Self::watch`], or should notifications stop,
+    /// this just waits for `timeout`.
+    pub async fn changed(&mut self, timeout: Duration) {
+        let Some((_, ref mut events)) = self.events else {
+            tokio::time::sleep(timeout).await;
+            return;
+        };
+        if let Ok(None) = tokio::time::timeout(timeout, events.recv()).await {
+            self.events = None;
+            return;
+        }
+        // A burst of events needs only one rescan
+        while events.try_recv().is_ok() {}
+    }
+
+    /// Record the files currently in the directory as already reported, so
+    /// only files created or changed from now on are picked up.
+    pub fn prime(&mut self) -> Result<()> {
+        self.reported = self.scan()?;
+        self.pending.clear();
+        Ok(())
+    }
+
+    /// Rescan the directory and return the files that have settled since
+    /// they were created or last changed, sorted by path.
+    pub fn poll(&mut self) -> Result<Vec<PathBuf>> {
+        self.poll_at(Instant::now())
+    }
+
+    /// Forget that `path` was reported, so it is reported again once it
+    /// settles, e.g. after a failed upload.
+    pub fn forget(&mut self, path: &Path) {
+        self.reported.remove(path);
+    }
+
+    fn poll_at(&mut self, now: Instant) -> Result<Vec<PathBuf>> {
+        let current = self.scan()?;
+        self.reported.retain(|path, _| current.contains_key(path));
+        self.pending.retain(|path, _| current.contains_key(path));
+
+        let mut ready = Vec::new();
+        for (path, snapshot) in current {
+            if self.reported.get(&path) == Some(&snapshot) {
+                self.pending.remove(&path);
+                continue;
+            }
+            match self.pending.get(&path) {
+                Some((seen, since)) if *seen == snapshot => {
+                    if now.duration_since(*since) >= self.debounce {
+                        self.pending.remove(&path);
+                        self.reported.insert(path.clone(), snapshot);
+                        ready.push(path);
+                    }
+                }
+                _ => {
+                    self.pending.insert(path, (snapshot, now));
+                }
+            }
+        }
+        ready.sort();
+        Ok(ready)
+    }
+
+    fn is_ignored(&self, name: &str) -> bool {
+        name.starts_with('.') || self.ignore.iter().any(|p| p.matches(name))
+    }
+
+    fn scan(&self) -> Result<HashMap<PathBuf, Snapshot>> {
+        let read_error = |source| DriveError::FileReadError {
+            path: self.root.display().to_string(),
+            source,
+        };
+        let mut files = HashMap::new();
+        for entry in fs::read_dir(&self.root).map_err(read_error)? {
+            let entry = entry.map_err(read_error)?;
+            let name = entry.file_name();
+            if self.is_ignored(&name.to_string_lossy()) {
+                continue;
+            }
+            // Files can vanish between listing and stat; they are simply skipped
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            let snapshot = Snapshot {
+                len: metadata.len(),
+                modified: metadata.modified().ok(),
+            };
+            files.insert(entry.path(), snapshot);
+        }
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dir_watcher() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("old.csv"), "old").unwrap();
+        let ignore = vec![Pattern::new("*.tmp").unwrap()];
+        let mut watcher = DirWatcher::new(dir.path(), Duration::from_secs(2), ignore);
+        watcher.prime().unwrap();
+
+        let start = Instant::now();
+        fs::write(dir.path().join("new.csv"), "new").unwrap();
+        fs::write(dir.path().join("scratch.tmp"), "x").unwrap();
+        fs::write(dir.path().join(".hidden"), "x").unwrap();
+        assert!(watcher.poll_at(start).unwrap().is_empty());
+        // Still within the debounce period
+        assert!(watcher.poll_at(start + Duration::from_secs(1)).unwrap().is_empty());
+        let ready = watcher.poll_at(start + Duration::from_secs(2)).unwrap();
+        assert_eq!(ready, vec![dir.path().join("new.csv")]);
+        assert!(watcher.poll_at(start + Duration::from_secs(3)).unwrap().is_empty());
+
+        // A changed file is reported again, and so is a forgotten one
+        fs::write(dir.path().join("old.csv"), "changed").unwrap();
+        watcher.forget(&dir.path().join("new.csv"));
+        let later = start + Duration::from_secs(10);
+        assert!(watcher.poll_at(later).unwrap().is_empty());
+        let ready = watcher.poll_at(later + Duration::from_secs(2)).unwrap();
+        assert_eq!(ready, vec![dir.path().join("new.csv"), dir.path().join("old.csv")]);
+    }
+
+    #[tokio::test]
+    async fn test_changed_wakes_on_notification() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut watcher = DirWatcher::new(dir.path(), Duration::ZERO, Vec::new());
+        watcher.watch().unwrap();
+
+        fs::write(dir.path().join("new.csv"), "new").unwrap();
+        let waited = tokio::time::timeout(
+            Duration::from_secs(30),
+            watcher.changed(Duration::from_secs(60)),
+        );
+        assert!(waited.await.is_ok());
+    }
+}
@@ -0,0 +1,204 @@
+//! Finding files with identical content below a folder.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+
+use crate::client::{SharedDriveClient, WalkOptions};
+use crate::error::Result;
+use crate::models::FileMetadata;
+
+/// One copy in a [`DuplicateSet`].
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateFile {
+    /// Path relative to the searched folder, with `/` as separator.
+    pub path: String,
+    pub file: FileMetadata,
+}
+
+/// Which copy of a [`DuplicateSet`] to keep when removing the others.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Keep {
+    /// The copy with the first path in sort order.
+    #[default]
+    First,
+    /// The most recently modified copy.
+    Newest,
+    /// The least recently modified copy.
+    Oldest,
+}
+
+/// Files with the same MD5 checksum and size.
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateSet {
+    pub md5_checksum: String,
+    /// Size of each copy in bytes.
+    pub size: u64,
+    /// The copies, sorted by path; always at least two.
+    pub files: Vec<DuplicateFile>,
+}
+
+impl DuplicateSet {
+    /// Bytes taken up by all copies but one.
+    pub fn wasted_bytes(&self) -> u64 {
+        self.size * (self.files.len() as u64 - 1)
+    }
+
+    /// Split the set into the copy to keep and the redundant others.
+    ///
+    /// Ties between equally old copies go to the first path.
+    pub fn split(&self, keep: Keep) -> (&DuplicateFile, Vec<&DuplicateFile>) {
+        // Drive formats all timestamps alike, so they order as strings
+        let modified = |i: usize| self.files[i].file.modified_time.as_deref();
+        let mut indices = 0..self.files.len();
+        let kept = match keep {
+            Keep::First => indices.next(),
+            Keep::Newest => indices.max_by(|&a, &b| modified(a).cmp(&modified(b)).then(b.cmp(&a))),
+            Keep::Oldest => indices.min_by_key(|&i| (modified(i).is_none(), modified(i))),
+        }
+        .unwrap_or(0);
+        let others = self
+            .files
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != kept)
+            .map(|(_, copy)| copy)
+            .collect();
+        (&self.files[kept], others)
+    }
+}
+
+/// Group walked `(relative_path, metadata)` entries, as returned by
+/// [`SharedDriveClient::walk_folder`], into sets of identical files.
+///
+/// Folders and files without a checksum (such as Google-native files) are
+/// ignored, as are further paths of a file that has several parents. Sets
+/// are ordered by wasted bytes, largest first.
+pub fn find_duplicates(entries: &[(String, FileMetadata)]) -> Vec<DuplicateSet> {
+    let mut groups: BTreeMap<(&str, u64), Vec<DuplicateFile>> = BTreeMap::new();
+    let mut seen = HashSet::new();
+    for (path, file) in entries {
+        if file.is_folder() || !seen.insert(file.id.as_str()) {
+            continue;
+        }
+        let (Some(md5), Some(size)) = (file.md5_checksum.as_deref(), file.size) else {
+            continue;
+        };
+        groups.entry((md5, size)).or_default().push(DuplicateFile {
+            path: path.clone(),
+            file: file.clone(),
+        });
+    }
+
+    let mut sets: Vec<DuplicateSet> = groups
+        .into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .map(|((md5, size), mut files)| {
+            files.sort_by(|a, b| a.path.cmp(&b.path));
+            DuplicateSet {
+                md5_checksum: md5.to_string(),
+                size,
+                files,
+            }
+        })
+        .collect();
+    sets.sort_by_key(|set| Reverse(set.wasted_bytes()));
+    sets
+}
+
+/// Walk the tree below `folder_id` and return its sets of identical files,
+/// as by [`find_duplicates`].
+pub async fn find_duplicates_in(
+    client: &SharedDriveClient,
+    folder_id: &str,
+) -> Result<Vec<DuplicateSet>> {
+    let entries = client.walk_folder(folder_id, &WalkOptions::default()).await?;
+    Ok(find_duplicates(&entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FOLDER_MIME_TYPE;
+
+    fn file(id: &str, md5: Option<&str>, size: u64) -> (String, FileMetadata) {
+        let file = FileMetadata {
+            id: id.to_string(),
+            name: id.to_string(),
+            md5_checksum: md5.map(str::to_string),
+            size: Some(size),
+            ..Default::default()
+        };
+        (format!("dir/{}", id), file)
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let mut folder = file("sub", None, 0);
+        folder.1.mime_type = Some(FOLDER_MIME_TYPE.to_string());
+        let entries = vec![
+            file("a", Some("aaa"), 10),
+            folder,
+            file("b", Some("bbb"), 100),
+            file("a2", Some("aaa"), 10),
+            file("b2", Some("bbb"), 100),
+            file("a3", Some("aaa"), 10),
+            file("c", Some("aaa"), 11),
+            file("doc", None, 10),
+        ];
+
+        let sets = find_duplicates(&entries);
+        assert_eq!(sets.len(), 2);
+        assert_eq!(sets[0].md5_checksum, "bbb");
+        assert_eq!(sets[0].wasted_bytes(), 100);
+        let paths: Vec<&str> = sets[1].files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["dir/a", "dir/a2", "dir/a3"]);
+        assert_eq!(sets[1].wasted_bytes(), 20);
+    }
+
+    #[test]
+    fn test_file_with_two_parents_is_not_a_duplicate() {
+        let mut other_path = file("a", Some("aaa"), 10);
+        other_path.0 = "other/a".to_string();
+        let entries = vec![file("a", Some("aaa"), 10), other_path];
+        assert!(find_duplicates(&entries).is_empty());
+
+        let entries = vec![
+            file("a", Some("aaa"), 10),
+            file("a", Some("aaa"), 10),
+            file("b", Some("aaa"), 10),
+        ];
+        let sets = find_duplicates(&entries);
+        assert_eq!(sets.len(), 1);
+        assert_eq!(sets[0].files.len(), 2);
+    }
+
+    #[test]
+    fn test_split() {
+        let mut entries = vec![
+            file("b", Some("aaa"), 10),
+            file("a", Some("aaa"), 10),
+            file("c", Some("aaa"), 10),
+        ];
+        entries[0].1.modified_time = Some("2024-03-01T00:00:00.000Z".to_string());
+        entries[1].1.modified_time = Some("2024-02-01T00:00:00.000Z".to_string());
+        entries[2].1.modified_time = Some("2024-03-01T00:00:00.000Z".to_string());
+        let set = &find_duplicates(&entries)[0];
+
+        let ids = |keep| {
+            let (kept, others) = set.split(keep);
+            let others: Vec<&str> = others.iter().map(|f| f.file.id.as_str()).collect();
+            (kept.file.id.as_str(), others)
+        };
+        assert_eq!(ids(Keep::First), ("a", vec!["b", "c"]));
+        assert_eq!(ids(Keep::Newest), ("b", vec!["a", "c"]));
+        assert_eq!(ids(Keep::Oldest), ("a", vec!["b", "c"]));
+    }
+
+    #[test]
+    fn test_no_duplicates() {
+        let entries = vec![file("a", Some("aaa"), 10), file("b", Some("bbb"), 10)];
+        assert!(find_duplicates(&entries).is_empty());
+    }
+}
@@ -1,7 +1,12 @@
 //! Error types for the share_drive crate.
 
+use std::time::Duration;
+
 use thiserror::Error;
 
+use crate::models::ApiErrorResponse;
+use crate::retry::is_retryable_status;
+
 /// Errors that can occur when interacting with Google Drive.
 #[derive(Error, Debug)]
 pub enum DriveError {
@@ -23,6 +28,12 @@ pub enum DriveError {
         source: std::io::Error,
     },
 
+    #[error("Failed to write output: {0}")]
+    OutputWriteError(std::io::Error),
+
+    #[error("Failed to read input: {0}")]
+    InputReadError(std::io::Error),
+
     #[error("Failed to parse credentials JSON: {0}")]
     CredentialsParseError(#[from] serde_json::Error),
 
@@ -32,12 +43,79 @@ pub enum DriveError {
     #[error("API error ({status}): {message}")]
     ApiError { status: u16, message: String },
 
+    #[error("Rate limit exceeded: {message}")]
+    RateLimited {
+        retry_after: Option<Duration>,
+        message: String,
+    },
+
     #[error("Invalid URL or ID: {0}")]
     InvalidUrlOrId(String),
 
+    #[error("'{0}' is a file, not a folder")]
+    NotAFolder(String),
+
+    #[error("Batch request failed: {0}")]
+    BatchRequestFailed(String),
+
     #[error("File not found: {0}")]
     FileNotFound(String),
 
+    #[error("Shared Drive not found: {0}")]
+    DriveNotFound(String),
+
+    #[error("'{name}' matches {count} Shared Drives; use the drive ID instead")]
+    AmbiguousDrive { name: String, count: usize },
+
+    #[error("'{path}' matches {count} files; use the file ID instead")]
+    AmbiguousPath { path: String, count: usize },
+
+    #[error("File '{name}' already exists in folder {parent_id}")]
+    FileExists { name: String, parent_id: String },
+
+    #[error("Checksum mismatch for '{path}': expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        path: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("'{name}' is a Google-native file ({mime_type}) and cannot be downloaded or exported")]
+    NotExportable { name: String, mime_type: String },
+
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+
+    #[error("Invalid color '{0}': expected a hex color such as #4285F4")]
+    InvalidColor(String),
+
+    #[error("Invalid request rate {0}: must be a positive number")]
+    InvalidRateLimit(String),
+
+    #[error("Invalid schedule: {0}")]
+    InvalidSchedule(String),
+
+    #[error("Invalid config file '{path}': {message}")]
+    InvalidConfig { path: String, message: String },
+
+    #[error("Hook '{command}' failed: {message}")]
+    HookFailed { command: String, message: String },
+
+    #[error("Alias '@{0}' is not defined; add it with `alias add`")]
+    AliasNotFound(String),
+
+    #[error("Invalid alias name '{0}': use letters, digits, '_', '-' and '.'")]
+    InvalidAlias(String),
+
+    #[error("Remote file name {0:?} is not a plain file name")]
+    UnsafeFileName(String),
+
+    #[error("Profile '{0}' is not defined in the config file")]
+    ProfileNotFound(String),
+
+    #[error("Invalid format template: {0}")]
+    InvalidTemplate(String),
+
     #[error("No files matched pattern: {0}")]
     NoFilesMatched(String),
 
@@ -52,6 +130,85 @@ pub enum DriveError {
 
     #[error("Token refresh failed: {0}")]
     TokenRefreshError(String),
+
+    #[error("Keyring error: {0}")]
+    KeyringError(String),
+
+    #[error("Cannot watch '{path}': {message}")]
+    WatchError { path: String, message: String },
+
+    #[error("Invalid certificate file '{path}': {message}")]
+    InvalidCertificate { path: String, message: String },
+
+    #[error("Invalid value for header {name}: {value:?}")]
+    InvalidHeader { name: String, value: String },
+
+    #[error("Failed to start the async runtime: {0}")]
+    RuntimeError(std::io::Error),
+}
+
+impl DriveError {
+    /// Build an [`DriveError::ApiError`] from an error response body, preferring
+    /// the structured Google error message when the body is JSON.
+    pub fn from_response_body(status: u16, body: &str) -> Self {
+        match serde_json::from_str::<ApiErrorResponse>(body) {
+            Ok(api_error) => DriveError::ApiError {
+                status: api_error.error.code,
+                message: api_error.error.message,
+            },
+            Err(_) => DriveError::ApiError {
+                status,
+                message: body.to_string(),
+            },
+        }
+    }
+
+    /// Whether the error means the caller is being throttled by the API.
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, DriveError::RateLimited { .. })
+    }
+
+    /// Whether the credentials could not be loaded or were rejected.
+    pub fn is_auth_error(&self) -> bool {
+        matches!(
+            self,
+            DriveError::AuthenticationError(_)
+                | DriveError::CredentialsFileError(_)
+                | DriveError::CredentialsParseError(_)
+                | DriveError::JwtError(_)
+                | DriveError::TokenRefreshError(_)
+                | DriveError::KeyringError(_)
+                | DriveError::ApiError { status: 401, .. }
+        )
+    }
+
+    /// Whether a file, drive or alias does not exist, or is not visible to
+    /// the caller.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            DriveError::FileNotFound(_)
+                | DriveError::DriveNotFound(_)
+                | DriveError::AliasNotFound(_)
+                | DriveError::ApiError { status: 404, .. }
+        )
+    }
+
+    /// Whether the caller lacks access to do what was asked.
+    pub fn is_permission_denied(&self) -> bool {
+        matches!(self, DriveError::ApiError { status: 403, .. })
+    }
+
+    /// Whether the server could not be reached or kept failing, so that
+    /// trying again later may succeed.
+    pub fn is_network_error(&self) -> bool {
+        match self {
+            DriveError::HttpError(e) => !e.is_builder() && !e.is_decode(),
+            DriveError::RateLimited { .. } => true,
+            DriveError::ApiError { status, .. } => is_retryable_status(*status),
+            _ => false,
+        }
+    }
 }
 
 /// Result type alias for DriveError.
@@ -0,0 +1,138 @@
+//! Conversions between Google-native files (Docs, Sheets, Slides, ...) and
+//! regular file formats.
+//!
+//! Google-native files have no binary content, so the media endpoint refuses
+//! to download them; they have to be exported to a regular format instead.
+//! In the other direction, office files can be converted on upload.
+
+/// Prefix shared by the MIME types of Google-native files.
+pub const GOOGLE_APPS_PREFIX: &str = "application/vnd.google-apps.";
+
+/// A format a Google-native file can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportFormat {
+    /// MIME type passed to the export endpoint.
+    pub mime_type: &'static str,
+    /// File extension for the exported file, without the dot.
+    pub extension: &'static str,
+}
+
+/// Whether `mime_type` denotes a Google-native file (including folders and shortcuts).
+pub fn is_google_native(mime_type: &str) -> bool {
+    mime_type.starts_with(GOOGLE_APPS_PREFIX)
+}
+
+/// Default export format for a Google-native MIME type.
+///
+/// Documents, spreadsheets and presentations become their Office
+/// equivalents. Returns `None` for types that cannot be exported, such as
+/// folders, shortcuts and forms.
+pub fn default_export_format(mime_type: &str) -> Option<ExportFormat> {
+    let (mime_type, extension) = match mime_type.strip_prefix(GOOGLE_APPS_PREFIX)? {
+        "document" => (
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            "docx",
+        ),
+        "spreadsheet" => (
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            "xlsx",
+        ),
+        "presentation" => (
+            "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+            "pptx",
+        ),
+        "drawing" => ("image/png", "png"),
+        "script" => ("application/vnd.google-apps.script+json", "json"),
+        "jam" => ("application/pdf", "pdf"),
+        _ => return None,
+    };
+    Some(ExportFormat {
+        mime_type,
+        extension,
+    })
+}
+
+/// Google-native MIME type an uploaded file of `mime_type` can be converted to.
+///
+/// Word processing and text files become Docs, spreadsheets and CSV/TSV
+/// become Sheets, and presentations become Slides. Returns `None` for
+/// formats Drive cannot convert.
+pub fn import_mime_type(mime_type: &str) -> Option<&'static str> {
+    let target = match mime_type {
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+        | "application/msword"
+        | "application/vnd.oasis.opendocument.text"
+        | "application/rtf"
+        | "text/rtf"
+        | "text/plain"
+        | "text/html" => "application/vnd.google-apps.document",
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+        | "application/vnd.ms-excel"
+        | "application/vnd.oasis.opendocument.spreadsheet"
+        | "text/csv"
+        | "text/tab-separated-values" => "application/vnd.google-apps.spreadsheet",
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation"
+        | "application/vnd.ms-powerpoint"
+        | "application/vnd.oasis.opendocument.presentation" => {
+            "application/vnd.google-apps.presentation"
+        }
+        _ => return None,
+    };
+    Some(target)
+}
+
+/// File name for an exported file: `name` with the format's extension
+/// appended, unless it already ends with it.
+pub fn export_file_name(name: &str, format: &ExportFormat) -> String {
+    let suffix = format!(".{}", format.extension);
+    if name.to_lowercase().ends_with(&suffix) {
+        name.to_string()
+    } else {
+        format!("{}{}", name, suffix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_export_format() {
+        let doc = default_export_format("application/vnd.google-apps.document").unwrap();
+        assert_eq!(doc.extension, "docx");
+        let sheet = default_export_format("application/vnd.google-apps.spreadsheet").unwrap();
+        assert_eq!(sheet.extension, "xlsx");
+
+        assert!(default_export_format("application/vnd.google-apps.folder").is_none());
+        assert!(default_export_format("application/vnd.google-apps.form").is_none());
+        assert!(default_export_format("application/pdf").is_none());
+    }
+
+    #[test]
+    fn test_export_file_name() {
+        let doc = default_export_format("application/vnd.google-apps.document").unwrap();
+        assert_eq!(export_file_name("Meeting notes", &doc), "Meeting notes.docx");
+        assert_eq!(export_file_name("report.DOCX", &doc), "report.DOCX");
+    }
+
+    #[test]
+    fn test_import_mime_type() {
+        assert_eq!(
+            import_mime_type("text/csv"),
+            Some("application/vnd.google-apps.spreadsheet")
+        );
+        assert_eq!(
+            import_mime_type(
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+            ),
+            Some("application/vnd.google-apps.document")
+        );
+        assert_eq!(import_mime_type("application/pdf"), None);
+    }
+
+    #[test]
+    fn test_is_google_native() {
+        assert!(is_google_native("application/vnd.google-apps.spreadsheet"));
+        assert!(!is_google_native("text/csv"));
+    }
+}
@@ -0,0 +1,236 @@
+//! Shell commands run after a file has been transferred.
+//!
+//! The command is run through the platform shell once per file. In the
+//! command, `{path}`, `{id}` and `{name}` are replaced by the (shell-quoted)
+//! local path, Drive file ID and file name. The file's metadata is also
+//! available in `SHARE_DRIVE_*` environment variables, see [`Hook::env`].
+
+use std::path::Path;
+
+use tokio::process::Command;
+
+use crate::error::{DriveError, Result};
+use crate::models::FileMetadata;
+
+/// What kind of transfer triggered a hook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    Upload,
+    Download,
+    /// A file copied by a scheduled sync job.
+    Sync,
+}
+
+impl HookEvent {
+    /// Value of `SHARE_DRIVE_EVENT` for this event.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookEvent::Upload => "upload",
+            HookEvent::Download => "download",
+            HookEvent::Sync => "sync",
+        }
+    }
+}
+
+/// Output of a hook that ran to completion.
+#[derive(Debug, Clone)]
+pub struct HookOutput {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+/// A command template run after each successful transfer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hook {
+    command: String,
+}
+
+impl Hook {
+    pub fn new<S: Into<String>>(command: S) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+
+    /// The command template as given.
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// The command with its placeholders filled in for one file.
+    ///
+    /// The template is scanned once, so placeholders inside substituted
+    /// values (such as a file named `{path}`) are left as they are.
+    pub fn command_line(&self, path: &Path, file: &FileMetadata) -> String {
+        let mut line = String::with_capacity(self.command.len());
+        let mut rest = self.command.as_str();
+        while let Some(start) = rest.find('{') {
+            line.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let value = if rest.starts_with("{path}") {
+                path.to_string_lossy()
+            } else if rest.starts_with("{id}") {
+                file.id.as_str().into()
+            } else if rest.starts_with("{name}") {
+                file.name.as_str().into()
+            } else {
+                line.push('{');
+                rest = &rest[1..];
+                continue;
+            };
+            line.push_str(&shell_quote(&value));
+            // Every placeholder ends at the first '}'
+            rest = &rest[rest.find('}').map_or(rest.len(), |end| end + 1)..];
+        }
+        line.push_str(rest);
+        line
+    }
+
+    /// Environment variables describing the transferred file.
+    ///
+    /// `SHARE_DRIVE_EVENT`, `SHARE_DRIVE_PATH`, `SHARE_DRIVE_FILE_ID` and
+    /// `SHARE_DRIVE_FILE_NAME` are always set; `SHARE_DRIVE_MIME_TYPE`,
+    /// `SHARE_DRIVE_SIZE`, `SHARE_DRIVE_MD5` and `SHARE_DRIVE_LINK` only when
+    /// Drive reported them.
+    pub fn env(
+        event: HookEvent,
+        path: &Path,
+        file: &FileMetadata,
+    ) -> Vec<(&'static str, String)> {
+        let mut env = vec![
+            ("SHARE_DRIVE_EVENT", event.as_str().to_string()),
+            ("SHARE_DRIVE_PATH", path.to_string_lossy().into_owned()),
+            ("SHARE_DRIVE_FILE_ID", file.id.clone()),
+            ("SHARE_DRIVE_FILE_NAME", file.name.clone()),
+        ];
+        let optional = [
+            ("SHARE_DRIVE_MIME_TYPE", file.mime_type.clone()),
+            ("SHARE_DRIVE_SIZE", file.size.map(|s| s.to_string())),
+            ("SHARE_DRIVE_MD5", file.md5_checksum.clone()),
+            ("SHARE_DRIVE_LINK", file.web_view_link.clone()),
+        ];
+        env.extend(optional.into_iter().filter_map(|(k, v)| Some((k, v?))));
+        env
+    }
+
+    /// Run the hook for one transferred file and wait for it to finish.
+    ///
+    /// Fails if the command cannot be started or exits unsuccessfully.
+    pub async fn run(
+        &self,
+        event: HookEvent,
+        path: &Path,
+        file: &FileMetadata,
+    ) -> Result<HookOutput> {
+        let command_line = self.command_line(path, file);
+        let output = shell(&command_line)
+            .envs(Self::env(event, path, file))
+            .output()
+            .await
+            .map_err(|e| DriveError::HookFailed {
+                command: command_line.clone(),
+                message: e.to_string(),
+            })?;
+
+        if !output.status.success() {
+            return Err(DriveError::HookFailed {
+                command: command_line,
+                message: format!(
+                    "{}: {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            });
+        }
+        Ok(HookOutput {
+            stdout: output.stdout,
+            stderr: output.stderr,
+        })
+    }
+}
+
+#[cfg(unix)]
+fn shell(command_line: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(command_line);
+    command
+}
+
+#[cfg(windows)]
+fn shell(command_line: &str) -> Command {
+    let mut command = Command::new("cmd");
+    command.arg("/C").arg(command_line);
+    command
+}
+
+/// Quote `value` as a single shell word.
+#[cfg(unix)]
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(windows)]
+fn shell_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file() -> FileMetadata {
+        FileMetadata {
+            id: "abc123".to_string(),
+            name: "it's.csv".to_string(),
+            size: Some(42),
+            ..FileMetadata::default()
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_line() {
+        let hook = Hook::new("notify {name} {id} {path}");
+        assert_eq!(
+            hook.command_line(Path::new("/data/it's.csv"), &file()),
+            r"notify 'it'\''s.csv' 'abc123' '/data/it'\''s.csv'"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_line_substitutes_once() {
+        let file = FileMetadata {
+            id: "{name}".to_string(),
+            name: "{path}; rm -rf {id}".to_string(),
+            ..FileMetadata::default()
+        };
+        let hook = Hook::new("notify {name} {id} {path} {other}");
+        assert_eq!(
+            hook.command_line(Path::new("/data/{id}"), &file),
+            "notify '{path}; rm -rf {id}' '{name}' '/data/{id}' {other}"
+        );
+    }
+
+    #[test]
+    fn test_hook_env() {
+        let env = Hook::env(HookEvent::Upload, Path::new("out.csv"), &file());
+        let get = |key| env.iter().find(|(k, _)| *k == key).map(|(_, v)| v.as_str());
+        assert_eq!(get("SHARE_DRIVE_EVENT"), Some("upload"));
+        assert_eq!(get("SHARE_DRIVE_FILE_ID"), Some("abc123"));
+        assert_eq!(get("SHARE_DRIVE_SIZE"), Some("42"));
+        assert_eq!(get("SHARE_DRIVE_MD5"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_hook() {
+        let hook = Hook::new("echo \"$SHARE_DRIVE_EVENT\" {name}");
+        let output = hook.run(HookEvent::Download, Path::new("x"), &file()).await.unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "download it's.csv\n");
+
+        let failing = Hook::new("exit 3");
+        let err = failing.run(HookEvent::Download, Path::new("x"), &file()).await;
+        assert!(matches!(err, Err(DriveError::HookFailed { .. })));
+    }
+}
@@ -0,0 +1,60 @@
+//! Secrets in the platform keyring.
+//!
+//! Entries are kept under the service name `share_drive` through the
+//! `keyring` crate: in the Keychain on macOS, the Credential Manager on
+//! Windows and the Secret Service on Linux and other Unix desktops. Where no
+//! keyring is available, every call fails with [`DriveError::KeyringError`].
+
+use ::keyring::{Entry, Error};
+
+use crate::error::{DriveError, Result};
+
+/// Service name of all entries.
+pub const SERVICE: &str = "share_drive";
+
+/// Store `secret` under `account`, replacing any previous value.
+pub fn store(account: &str, secret: &str) -> Result<()> {
+    entry(account)?.set_password(secret).map_err(keyring_error)
+}
+
+/// The secret stored under `account`.
+pub fn load(account: &str) -> Result<String> {
+    entry(account)?.get_password().map_err(|e| match e {
+        Error::NoEntry => DriveError::KeyringError(format!("no entry for {}", account)),
+        e => keyring_error(e),
+    })
+}
+
+/// Remove the secret stored under `account`, if any.
+pub fn delete(account: &str) -> Result<()> {
+    match entry(account)?.delete_credential() {
+        Ok(()) | Err(Error::NoEntry) => Ok(()),
+        Err(e) => Err(keyring_error(e)),
+    }
+}
+
+fn entry(account: &str) -> Result<Entry> {
+    Entry::new(SERVICE, account).map_err(keyring_error)
+}
+
+fn keyring_error(e: Error) -> DriveError {
+    DriveError::KeyringError(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_entry() {
+        // The mock keeps each entry's secret to itself, away from the real keyring
+        ::keyring::set_default_credential_builder(::keyring::mock::default_credential_builder());
+        let account = "someone@example.com";
+        match load(account) {
+            Err(DriveError::KeyringError(message)) => assert!(message.contains(account)),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(delete(account).is_ok());
+        assert!(store(account, "1//refresh").is_ok());
+    }
+}
@@ -4,6 +4,7 @@
 //! - List files in a Shared Drive folder
 //! - Upload files to a Shared Drive folder (with glob pattern support)
 //! - Download files from Shared Drive to local filesystem
+//! - Verify a local directory against a Shared Drive folder
 //!
 //! # Example
 //!
@@ -24,15 +25,66 @@
 //! }
 //! ```
 
+pub mod alias;
 pub mod auth;
+pub mod batch;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod changes;
+pub mod checksum;
 pub mod client;
+pub mod config;
+pub mod dir_watch;
+pub mod dupes;
 pub mod error;
+pub mod export;
+pub mod hook;
+pub mod http_log;
+pub mod keyring;
+pub mod metadata_cache;
 pub mod models;
+pub mod name_cache;
+pub mod oauth;
+pub mod progress;
+pub mod query;
+pub mod retry;
+pub mod schedule;
+pub mod session;
+pub mod stats;
+pub mod template;
+pub mod throttle;
 pub mod url_parser;
+pub mod verify;
 
 // Re-exports for convenience
-pub use auth::Authenticator;
-pub use client::{ProgressCallback, SharedDriveClient, TransferProgress, UploadProgress};
+pub use alias::AliasStore;
+pub use auth::{Authenticator, Scope};
+pub use changes::PageTokenStore;
+pub use checksum::md5_file;
+pub use client::{
+    ClientBuilder, ConflictPolicy, DownloadOptions, ListOptions, ProgressCallback,
+    SharedDriveClient, TransferProgress, UploadAction, UploadOptions, UploadOutcome, UploadProgress,
+    WalkOptions,
+};
+pub use config::{Config, Profile, SyncDirection, SyncJob};
+pub use dir_watch::DirWatcher;
+pub use dupes::{find_duplicates, find_duplicates_in, DuplicateFile, DuplicateSet, Keep};
 pub use error::{DriveError, Result};
-pub use models::{format_eta, format_size, FileMetadata};
-pub use url_parser::extract_id;
+pub use export::{default_export_format, ExportFormat};
+pub use hook::{Hook, HookEvent};
+pub use metadata_cache::MetadataCache;
+pub use models::{
+    format_eta, format_size, parse_size, FileMetadata, Permission, PermissionRole, PermissionType,
+};
+pub use name_cache::NameCache;
+pub use oauth::{ClientSecrets, DeviceLogin, LoopbackLogin};
+pub use progress::{BatchProgress, TransferEvent};
+pub use query::{escape_query_value, DriveQuery, FileFilter, FileKind, TrashFilter};
+pub use retry::RetryPolicy;
+pub use schedule::Schedule;
+pub use session::SessionStore;
+pub use stats::{EndpointStats, TransferStats};
+pub use template::FormatTemplate;
+pub use throttle::{parse_bandwidth, BandwidthLimiter, RequestRateLimiter};
+pub use url_parser::{extract_id, extract_id_with_resource_key, extract_ref, DocKind, DriveRef};
+pub use verify::{verify_folder, VerifyReport};
@@ -0,0 +1,199 @@
+//! Optional cache of file metadata for [`SharedDriveClient`](crate::SharedDriveClient).
+//!
+//! With a cache attached, `get_file` and `find_file` answer repeated lookups
+//! from memory until the entries expire. Files the client changes or deletes
+//! are evicted, but changes made by others only show up once the TTL has
+//! passed, so keep it short for long-running processes.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DriveError, Result};
+use crate::models::FileMetadata;
+use crate::session::unix_now;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedFile {
+    file: FileMetadata,
+    /// When the entry was stored (seconds since the Unix epoch).
+    cached_at: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Entries {
+    /// Results of `get_file`, by file ID.
+    files: HashMap<String, CachedFile>,
+    /// Results of `find_file`, by parent ID and name.
+    names: HashMap<String, CachedFile>,
+}
+
+/// File metadata cache with a time to live, kept in memory and optionally
+/// saved to a JSON file between runs.
+#[derive(Debug)]
+pub struct MetadataCache {
+    ttl: Duration,
+    path: Option<PathBuf>,
+    entries: Mutex<Entries>,
+}
+
+impl MetadataCache {
+    /// An in-memory cache whose entries are valid for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            path: None,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// A cache loaded from `path`, if it exists, and written back by
+    /// [`Self::save`]. Expired entries are dropped on load, and a missing or
+    /// corrupt file starts an empty cache.
+    pub fn open<P: Into<PathBuf>>(path: P, ttl: Duration) -> Self {
+        let path = path.into();
+        let mut entries: Entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        let now = unix_now();
+        entries.files.retain(|_, entry| !is_expired(entry, ttl, now));
+        entries.names.retain(|_, entry| !is_expired(entry, ttl, now));
+        Self {
+            ttl,
+            path: Some(path),
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// File the cache is saved to, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Cached metadata of the file with `file_id`.
+    pub fn file(&self, file_id: &str) -> Option<FileMetadata> {
+        let entries = self.entries.lock().unwrap();
+        self.fresh(entries.files.get(file_id))
+    }
+
+    /// Cached metadata of the file called `name` in folder `parent_id`.
+    pub fn named(&self, parent_id: &str, name: &str) -> Option<FileMetadata> {
+        let entries = self.entries.lock().unwrap();
+        self.fresh(entries.names.get(&name_key(parent_id, name)))
+    }
+
+    /// Remember the full metadata of a file, as returned by `get_file`.
+    pub fn insert_file(&self, file: &FileMetadata) {
+        let entry = CachedFile {
+            file: file.clone(),
+            cached_at: unix_now(),
+        };
+        self.entries.lock().unwrap().files.insert(file.id.clone(), entry);
+    }
+
+    /// Remember that `file` is the one called `file.name` in folder `parent_id`.
+    pub fn insert_named(&self, parent_id: &str, file: &FileMetadata) {
+        let entry = CachedFile {
+            file: file.clone(),
+            cached_at: unix_now(),
+        };
+        let key = name_key(parent_id, &file.name);
+        self.entries.lock().unwrap().names.insert(key, entry);
+    }
+
+    /// Evict everything cached about the file with `file_id`.
+    pub fn invalidate(&self, file_id: &str) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.files.remove(file_id);
+        entries.names.retain(|_, entry| entry.file.id != file_id);
+    }
+
+    /// Evict all entries.
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.files.clear();
+        entries.names.clear();
+    }
+
+    /// Write the cache to its file; a no-op for in-memory caches.
+    pub fn save(&self) -> Result<()> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        let write_err = |e| DriveError::FileWriteError {
+            path: path.display().to_string(),
+            source: e,
+        };
+        let content = serde_json::to_string(&*self.entries.lock().unwrap())?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(write_err)?;
+        }
+        fs::write(path, content).map_err(write_err)
+    }
+
+    fn fresh(&self, entry: Option<&CachedFile>) -> Option<FileMetadata> {
+        let entry = entry?;
+        (!is_expired(entry, self.ttl, unix_now())).then(|| entry.file.clone())
+    }
+}
+
+fn name_key(parent_id: &str, name: &str) -> String {
+    format!("{}/{}", parent_id, name)
+}
+
+fn is_expired(entry: &CachedFile, ttl: Duration, now: u64) -> bool {
+    now.saturating_sub(entry.cached_at) >= ttl.as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(id: &str, name: &str) -> FileMetadata {
+        FileMetadata {
+            id: id.to_string(),
+            name: name.to_string(),
+            ..FileMetadata::default()
+        }
+    }
+
+    #[test]
+    fn test_metadata_cache() {
+        let cache = MetadataCache::new(Duration::from_secs(60));
+        cache.insert_file(&file("1a", "a.txt"));
+        cache.insert_named("folder", &file("1a", "a.txt"));
+        cache.insert_named("folder", &file("1b", "b.txt"));
+        assert_eq!(cache.file("1a").unwrap().name, "a.txt");
+        assert_eq!(cache.named("folder", "b.txt").unwrap().id, "1b");
+        assert!(cache.named("other", "b.txt").is_none());
+
+        cache.invalidate("1a");
+        assert!(cache.file("1a").is_none());
+        assert!(cache.named("folder", "a.txt").is_none());
+        assert!(cache.named("folder", "b.txt").is_some());
+
+        let expired = MetadataCache::new(Duration::ZERO);
+        expired.insert_file(&file("1a", "a.txt"));
+        assert!(expired.file("1a").is_none());
+    }
+
+    #[test]
+    fn test_metadata_cache_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("metadata.json");
+        let ttl = Duration::from_secs(60);
+
+        let cache = MetadataCache::open(&path, ttl);
+        cache.insert_named("folder", &file("1a", "a.txt"));
+        cache.save().unwrap();
+
+        let reopened = MetadataCache::open(&path, ttl);
+        assert_eq!(reopened.named("folder", "a.txt").unwrap().id, "1a");
+        assert!(MetadataCache::open(&path, Duration::ZERO).named("folder", "a.txt").is_none());
+    }
+}
@@ -1,9 +1,17 @@
 //! Data models for Google Drive API responses.
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+/// MIME type Google Drive uses for folders.
+pub const FOLDER_MIME_TYPE: &str = "application/vnd.google-apps.folder";
+
+/// MIME type Google Drive uses for shortcuts.
+pub const SHORTCUT_MIME_TYPE: &str = "application/vnd.google-apps.shortcut";
+
 /// Metadata for a file or folder in Google Drive.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FileMetadata {
     pub id: String,
@@ -14,6 +22,104 @@ pub struct FileMetadata {
     pub web_view_link: Option<String>,
     #[serde(default, deserialize_with = "deserialize_size")]
     pub size: Option<u64>,
+    #[serde(default)]
+    pub md5_checksum: Option<String>,
+    #[serde(default)]
+    pub sha256_checksum: Option<String>,
+    /// Creation time as an RFC 3339 timestamp.
+    #[serde(default)]
+    pub created_time: Option<String>,
+    /// Last modification time as an RFC 3339 timestamp.
+    #[serde(default)]
+    pub modified_time: Option<String>,
+    /// IDs of the parent folders.
+    #[serde(default)]
+    pub parents: Vec<String>,
+    /// Owners of the file. Always empty for items in a Shared Drive.
+    #[serde(default)]
+    pub owners: Vec<User>,
+    #[serde(default)]
+    pub trashed: bool,
+    /// Whether the authenticated account has starred the file.
+    #[serde(default)]
+    pub starred: bool,
+    /// Target of a shortcut; only set when `mime_type` is the shortcut type.
+    #[serde(default)]
+    pub shortcut_details: Option<ShortcutDetails>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Color of a folder as `#RRGGBB`. Not set for files.
+    #[serde(default)]
+    pub folder_color_rgb: Option<String>,
+    /// Custom key-value properties private to the application, e.g. build numbers.
+    #[serde(default)]
+    pub app_properties: BTreeMap<String, String>,
+    /// ID of the current revision. Only set for files with binary content.
+    #[serde(default)]
+    pub head_revision_id: Option<String>,
+    /// User who last modified the file. Only requested by `get_file`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modifying_user: Option<User>,
+    /// What the caller may do with the file. Only requested by `get_file`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<Capabilities>,
+}
+
+/// Actions the authenticated account may perform on a file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    #[serde(default)]
+    pub can_edit: bool,
+    #[serde(default)]
+    pub can_download: bool,
+    #[serde(default)]
+    pub can_copy: bool,
+    #[serde(default)]
+    pub can_rename: bool,
+    #[serde(default)]
+    pub can_share: bool,
+    #[serde(default)]
+    pub can_trash: bool,
+    #[serde(default)]
+    pub can_delete: bool,
+    #[serde(default)]
+    pub can_add_children: bool,
+}
+
+impl Capabilities {
+    /// Names of the granted capabilities, e.g. `["edit", "download"]`.
+    pub fn granted(&self) -> Vec<&'static str> {
+        [
+            (self.can_edit, "edit"),
+            (self.can_download, "download"),
+            (self.can_copy, "copy"),
+            (self.can_rename, "rename"),
+            (self.can_share, "share"),
+            (self.can_trash, "trash"),
+            (self.can_delete, "delete"),
+            (self.can_add_children, "addChildren"),
+        ]
+        .into_iter()
+        .filter_map(|(granted, name)| granted.then_some(name))
+        .collect()
+    }
+}
+
+/// Target of a Drive shortcut.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutDetails {
+    pub target_id: String,
+    #[serde(default)]
+    pub target_mime_type: Option<String>,
+}
+
+impl FileMetadata {
+    /// Whether this entry is a folder.
+    pub fn is_folder(&self) -> bool {
+        self.mime_type.as_deref() == Some(FOLDER_MIME_TYPE)
+    }
 }
 
 fn deserialize_size<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
@@ -31,7 +137,7 @@ impl std::fmt::Display for FileMetadata {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let size_str = self
             .size
-            .map(|s| format_size(s))
+            .map(format_size)
             .unwrap_or_else(|| "-".to_string());
         let mime = self.mime_type.as_deref().unwrap_or("-");
         write!(f, "{}\t{}\t{}\t{}", self.id, size_str, mime, self.name)
@@ -55,6 +161,29 @@ pub fn format_size(bytes: u64) -> String {
     }
 }
 
+/// Parse a size such as `10M`, `1.5G`, `500KB` or `1048576` into bytes.
+///
+/// Suffixes are binary (K = 1024) and case-insensitive, with an optional
+/// trailing `B`. Returns `None` for malformed or negative values.
+pub fn parse_size(input: &str) -> Option<u64> {
+    let s = input.trim();
+    let s = s.strip_suffix(['B', 'b']).unwrap_or(s);
+
+    let (number, multiplier) = match s.chars().last()?.to_ascii_uppercase() {
+        'K' => (&s[..s.len() - 1], 1024u64),
+        'M' => (&s[..s.len() - 1], 1024 * 1024),
+        'G' => (&s[..s.len() - 1], 1024 * 1024 * 1024),
+        'T' => (&s[..s.len() - 1], 1024 * 1024 * 1024 * 1024),
+        _ => (s, 1),
+    };
+
+    let value: f64 = number.trim().parse().ok()?;
+    if !value.is_finite() || value < 0.0 {
+        return None;
+    }
+    Some((value * multiplier as f64).round() as u64)
+}
+
 /// Format seconds into human-readable time (e.g., "2m 15s", "1h 5m", "< 1s").
 pub fn format_eta(seconds: f64) -> String {
     if !seconds.is_finite() || seconds < 0.0 {
@@ -91,22 +220,40 @@ pub struct FileListResponse {
 }
 
 /// Shared Drive metadata.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Drive {
     pub id: String,
     pub name: String,
 }
 
-/// User information from the about API.
+/// Response from the drives.list API endpoint.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriveListResponse {
+    #[serde(default)]
+    pub drives: Vec<Drive>,
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
+/// Response from the files.generateIds API endpoint.
 #[derive(Debug, Deserialize)]
+pub struct GeneratedIds {
+    #[serde(default)]
+    pub ids: Vec<String>,
+}
+
+/// User information from the about API and file owner lists.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct User {
     pub email_address: Option<String>,
     pub display_name: Option<String>,
 }
 
-/// Storage quota information.
-#[derive(Debug, Deserialize)]
+/// Storage quota information. Values are byte counts as decimal strings;
+/// `limit` is absent when storage is unlimited.
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StorageQuota {
     pub limit: Option<String>,
@@ -116,13 +263,211 @@ pub struct StorageQuota {
 }
 
 /// About response from the Drive API.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct About {
     pub user: User,
     pub storage_quota: StorageQuota,
 }
 
+/// One entry of the changes feed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Change {
+    /// `file` or `drive`.
+    #[serde(default)]
+    pub change_type: Option<String>,
+    /// When the change happened, as an RFC 3339 timestamp.
+    #[serde(default)]
+    pub time: Option<String>,
+    /// Whether the file was removed (deleted, or no longer accessible).
+    #[serde(default)]
+    pub removed: bool,
+    #[serde(default)]
+    pub file_id: Option<String>,
+    /// Current metadata of the file; absent when `removed` is set.
+    #[serde(default)]
+    pub file: Option<FileMetadata>,
+}
+
+/// A push notification channel registered with `changes.watch`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Channel {
+    /// ID chosen by the client when registering.
+    pub id: String,
+    /// ID of the watched resource, needed to stop the channel.
+    pub resource_id: String,
+    #[serde(default)]
+    pub resource_uri: Option<String>,
+    /// Expiry in milliseconds since the Unix epoch, as a decimal string.
+    #[serde(default)]
+    pub expiration: Option<String>,
+}
+
+impl Channel {
+    /// When Drive stops sending notifications to this channel.
+    pub fn expires_at(&self) -> Option<std::time::SystemTime> {
+        let millis: u64 = self.expiration.as_deref()?.parse().ok()?;
+        Some(std::time::UNIX_EPOCH + std::time::Duration::from_millis(millis))
+    }
+}
+
+/// Response from the changes.list API endpoint.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeList {
+    #[serde(default)]
+    pub changes: Vec<Change>,
+    /// Token of the next page of this pull.
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+    /// Token for the next pull; only present on the last page.
+    #[serde(default)]
+    pub new_start_page_token: Option<String>,
+}
+
+/// A stored version of a file's content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Revision {
+    pub id: String,
+    #[serde(default)]
+    pub modified_time: Option<String>,
+    /// Whether the revision is exempt from automatic purging after 30 days.
+    #[serde(default)]
+    pub keep_forever: bool,
+    #[serde(default, deserialize_with = "deserialize_size")]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub md5_checksum: Option<String>,
+}
+
+/// A comment on a file, with its replies.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Comment {
+    pub id: String,
+    /// Plain-text content.
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub author: Option<User>,
+    #[serde(default)]
+    pub created_time: Option<String>,
+    #[serde(default)]
+    pub modified_time: Option<String>,
+    /// Whether the discussion has been marked resolved.
+    #[serde(default)]
+    pub resolved: bool,
+    #[serde(default)]
+    pub replies: Vec<Reply>,
+}
+
+/// A reply to a [`Comment`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reply {
+    pub id: String,
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub author: Option<User>,
+    #[serde(default)]
+    pub created_time: Option<String>,
+}
+
+/// Access level granted by a [`Permission`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionRole {
+    Reader,
+    Commenter,
+    Writer,
+    FileOrganizer,
+    Organizer,
+    Owner,
+}
+
+/// Who a [`Permission`] grants access to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionType {
+    User,
+    Group,
+    Domain,
+    /// Anyone with the link.
+    Anyone,
+}
+
+/// A permission on a file, as sent to and returned by the permissions API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Permission {
+    /// Assigned by Drive; `None` for a permission that is being created.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: PermissionType,
+    pub role: PermissionRole,
+    /// Grantee of a `user` or `group` permission.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email_address: Option<String>,
+    /// Grantee of a `domain` permission.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    /// RFC 3339 time at which the permission is removed automatically.
+    /// Only supported for `user` and `group` permissions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration_time: Option<String>,
+}
+
+impl Permission {
+    /// Access for anyone who has the link.
+    pub fn anyone(role: PermissionRole) -> Self {
+        Self {
+            id: None,
+            kind: PermissionType::Anyone,
+            role,
+            email_address: None,
+            domain: None,
+            expiration_time: None,
+        }
+    }
+
+    /// Access for the user with this email address.
+    pub fn user(email: &str, role: PermissionRole) -> Self {
+        Self {
+            id: None,
+            kind: PermissionType::User,
+            role,
+            email_address: Some(email.to_string()),
+            domain: None,
+            expiration_time: None,
+        }
+    }
+}
+
+/// Response from the permissions.list API endpoint.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionListResponse {
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
+/// Response from the comments.list API endpoint.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentListResponse {
+    #[serde(default)]
+    pub comments: Vec<Comment>,
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
 /// Google API error response.
 #[derive(Debug, Deserialize)]
 pub struct ApiErrorResponse {
@@ -133,6 +478,17 @@ pub struct ApiErrorResponse {
 pub struct ApiErrorDetail {
     pub code: u16,
     pub message: String,
+    #[serde(default)]
+    pub errors: Vec<ApiErrorItem>,
+}
+
+/// Individual error entry with a machine-readable reason (e.g. `userRateLimitExceeded`).
+#[derive(Debug, Deserialize)]
+pub struct ApiErrorItem {
+    #[serde(default)]
+    pub reason: String,
+    #[serde(default)]
+    pub message: String,
 }
 
 /// Service account credentials from JSON file.
@@ -143,18 +499,89 @@ pub struct ServiceAccountCredentials {
     pub token_uri: Option<String>,
 }
 
+/// Refresh token of a Google account signed in with `auth login`.
+///
+/// Stored in the `authorized_user` format used by Google's own tools, with
+/// the account's email address added for display.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorizedUser {
+    /// Always `authorized_user`; tells these files apart from service account keys.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// Empty when the token is kept in the keyring instead.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub refresh_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_uri: Option<String>,
+    /// Email address of the signed-in account, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    /// Whether the refresh token is stored in the platform keyring, under
+    /// [`Self::keyring_account`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keyring: bool,
+}
+
+impl AuthorizedUser {
+    /// Name of the keyring entry holding the refresh token.
+    pub fn keyring_account(&self) -> &str {
+        self.account.as_deref().unwrap_or(&self.client_id)
+    }
+}
+
 /// OAuth2 token response.
 #[derive(Debug, Deserialize)]
 pub struct TokenResponse {
     pub access_token: String,
     pub token_type: String,
     pub expires_in: u64,
+    /// Only returned when exchanging an authorization code.
+    #[serde(default)]
+    pub refresh_token: Option<String>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_permission_json() {
+        let permission = Permission::anyone(PermissionRole::Reader);
+        assert_eq!(
+            serde_json::to_value(&permission).unwrap(),
+            serde_json::json!({"type": "anyone", "role": "reader"})
+        );
+
+        let created: Permission = serde_json::from_str(
+            r#"{"id": "p1", "type": "user", "role": "fileOrganizer", "emailAddress": "a@b.com"}"#,
+        )
+        .unwrap();
+        assert_eq!(created.kind, PermissionType::User);
+        assert_eq!(created.role, PermissionRole::FileOrganizer);
+        assert_eq!(created.email_address.as_deref(), Some("a@b.com"));
+
+        let mut expiring = Permission::user("a@b.com", PermissionRole::Reader);
+        expiring.expiration_time = Some("2025-01-01T00:00:00Z".to_string());
+        assert_eq!(
+            serde_json::to_value(&expiring).unwrap()["expirationTime"],
+            "2025-01-01T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn test_channel_expires_at() {
+        let channel: Channel = serde_json::from_str(
+            r#"{"id": "c1", "resourceId": "r1", "expiration": "1700000000123"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            channel.expires_at(),
+            Some(std::time::UNIX_EPOCH + std::time::Duration::from_millis(1_700_000_000_123))
+        );
+    }
+
     #[test]
     fn test_format_size() {
         assert_eq!(format_size(500), "500 B");
@@ -164,6 +591,19 @@ mod tests {
         assert_eq!(format_size(1073741824), "1.00 GB");
     }
 
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("0"), Some(0));
+        assert_eq!(parse_size("512"), Some(512));
+        assert_eq!(parse_size("10M"), Some(10 * 1024 * 1024));
+        assert_eq!(parse_size("1.5g"), Some(1536 * 1024 * 1024));
+        assert_eq!(parse_size("500KB"), Some(500 * 1024));
+        assert_eq!(parse_size("2T"), Some(2 * 1024u64.pow(4)));
+        assert_eq!(parse_size("-1K"), None);
+        assert_eq!(parse_size("big"), None);
+        assert_eq!(parse_size(""), None);
+    }
+
     #[test]
     fn test_format_eta() {
         assert_eq!(format_eta(0.4), "< 1s");
@@ -190,6 +630,51 @@ mod tests {
         assert_eq!(metadata.name, "test.txt");
         assert_eq!(metadata.mime_type, Some("text/plain".to_string()));
         assert_eq!(metadata.size, Some(1024));
+        assert!(metadata.parents.is_empty());
+        assert!(!metadata.trashed);
+    }
+
+    #[test]
+    fn test_file_metadata_deserialize_extended() {
+        let json = r#"{
+            "id": "sc1",
+            "name": "link to report",
+            "mimeType": "application/vnd.google-apps.shortcut",
+            "createdTime": "2024-01-02T03:04:05.000Z",
+            "modifiedTime": "2024-02-03T04:05:06.000Z",
+            "sha256Checksum": "e3b0c442",
+            "parents": ["folder1"],
+            "owners": [{"displayName": "Ada", "emailAddress": "ada@example.com"}],
+            "trashed": true,
+            "shortcutDetails": {"targetId": "abc123", "targetMimeType": "application/pdf"}
+        }"#;
+
+        let metadata: FileMetadata = serde_json::from_str(json).unwrap();
+        assert_eq!(metadata.created_time.as_deref(), Some("2024-01-02T03:04:05.000Z"));
+        assert_eq!(metadata.modified_time.as_deref(), Some("2024-02-03T04:05:06.000Z"));
+        assert_eq!(metadata.sha256_checksum.as_deref(), Some("e3b0c442"));
+        assert_eq!(metadata.parents, vec!["folder1"]);
+        assert_eq!(metadata.owners[0].email_address.as_deref(), Some("ada@example.com"));
+        assert!(metadata.trashed);
+        assert_eq!(metadata.shortcut_details.unwrap().target_id, "abc123");
+    }
+
+    #[test]
+    fn test_capabilities_granted() {
+        let json = r#"{"canEdit": true, "canDownload": true, "canDelete": false}"#;
+        let capabilities: Capabilities = serde_json::from_str(json).unwrap();
+        assert_eq!(capabilities.granted(), vec!["edit", "download"]);
+    }
+
+    #[test]
+    fn test_file_metadata_is_folder() {
+        let json = r#"{"id": "f1", "name": "dir", "mimeType": "application/vnd.google-apps.folder"}"#;
+        let metadata: FileMetadata = serde_json::from_str(json).unwrap();
+        assert!(metadata.is_folder());
+
+        let json = r#"{"id": "f2", "name": "a.txt", "mimeType": "text/plain"}"#;
+        let metadata: FileMetadata = serde_json::from_str(json).unwrap();
+        assert!(!metadata.is_folder());
     }
 
     #[test]
@@ -200,6 +685,8 @@ mod tests {
             mime_type: Some("text/plain".to_string()),
             web_view_link: None,
             size: Some(1024),
+            md5_checksum: None,
+            ..Default::default()
         };
 
         let display = format!("{}", metadata);
@@ -207,4 +694,24 @@ mod tests {
         assert!(display.contains("test.txt"));
         assert!(display.contains("1.00 KB"));
     }
+
+    #[test]
+    fn test_authorized_user_in_keyring() {
+        let user = AuthorizedUser {
+            kind: "authorized_user".to_string(),
+            client_id: "123.apps.googleusercontent.com".to_string(),
+            client_secret: "secret".to_string(),
+            refresh_token: String::new(),
+            token_uri: None,
+            account: Some("me@example.com".to_string()),
+            keyring: true,
+        };
+        let json = serde_json::to_value(&user).unwrap();
+        assert!(json.get("refresh_token").is_none());
+        assert_eq!(json["keyring"], true);
+
+        let parsed: AuthorizedUser = serde_json::from_value(json).unwrap();
+        assert!(parsed.keyring);
+        assert_eq!(parsed.keyring_account(), "me@example.com");
+    }
 }
@@ -0,0 +1,198 @@
+//! Cache of file IDs by parent folder and name.
+//!
+//! Resolving a `drive:/` path costs one `files.list` call per component.
+//! The cache remembers each (parent, name) → ID lookup, optionally on disk
+//! so later runs can skip the round trips too. Entries expire after a TTL.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DriveError, Result};
+use crate::session::unix_now;
+
+/// How long entries stay valid unless configured otherwise.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedName {
+    id: String,
+    /// When the entry was stored (seconds since the Unix epoch).
+    cached_at: u64,
+}
+
+/// (parent, name) → file ID cache, in memory or backed by a JSON file.
+#[derive(Debug)]
+pub struct NameCache {
+    path: Option<PathBuf>,
+    /// `None` keeps entries forever.
+    ttl: Option<Duration>,
+    entries: Mutex<HashMap<String, CachedName>>,
+}
+
+impl Default for NameCache {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+impl NameCache {
+    /// A cache that lives only as long as the value and never expires entries.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            ttl: None,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// A cache persisted at `path`, loading entries younger than `ttl`.
+    ///
+    /// A missing or corrupt file starts an empty cache.
+    pub fn open<P: Into<PathBuf>>(path: P, ttl: Duration) -> Self {
+        let path = path.into();
+        let mut entries: HashMap<String, CachedName> = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        let now = unix_now();
+        entries.retain(|_, entry| !is_expired(entry, Some(ttl), now));
+        Self {
+            path: Some(path),
+            ttl: Some(ttl),
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// Default file: `$XDG_CACHE_HOME/share_drive/names.json`,
+    /// falling back to `~/.cache/share_drive/names.json`.
+    pub fn default_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))?;
+        Some(base.join("share_drive").join("names.json"))
+    }
+
+    /// File the cache is persisted to, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Number of cached entries.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Whether the cache holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The cached ID of `name` in folder `parent_id`, unless it has expired.
+    pub fn get(&self, parent_id: &str, name: &str) -> Option<String> {
+        let entries = self.entries.lock().unwrap();
+        let entry = entries.get(&key(parent_id, name))?;
+        (!is_expired(entry, self.ttl, unix_now())).then(|| entry.id.clone())
+    }
+
+    /// Remember the ID of `name` in folder `parent_id`.
+    pub fn insert(&self, parent_id: &str, name: &str, id: &str) -> Result<()> {
+        let entry = CachedName {
+            id: id.to_string(),
+            cached_at: unix_now(),
+        };
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(key(parent_id, name), entry);
+        self.save(&entries)
+    }
+
+    /// Forget the entry for `name` in folder `parent_id`, e.g. because it went stale.
+    pub fn remove(&self, parent_id: &str, name: &str) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.remove(&key(parent_id, name)).is_some() {
+            self.save(&entries)?;
+        }
+        Ok(())
+    }
+
+    /// Remove every entry, returning how many there were.
+    pub fn clear(&self) -> Result<usize> {
+        let mut entries = self.entries.lock().unwrap();
+        let count = entries.len();
+        entries.clear();
+        self.save(&entries)?;
+        Ok(count)
+    }
+
+    fn save(&self, entries: &HashMap<String, CachedName>) -> Result<()> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        let write_err = |e| DriveError::FileWriteError {
+            path: path.display().to_string(),
+            source: e,
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(write_err)?;
+        }
+        fs::write(path, serde_json::to_string(entries)?).map_err(write_err)
+    }
+}
+
+/// Drive IDs never contain `/`, so the key is unambiguous even for names that do.
+fn key(parent_id: &str, name: &str) -> String {
+    format!("{}/{}", parent_id, name)
+}
+
+fn is_expired(entry: &CachedName, ttl: Option<Duration>, now: u64) -> bool {
+    ttl.is_some_and(|ttl| now.saturating_sub(entry.cached_at) >= ttl.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_cache_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("names.json");
+
+        let cache = NameCache::open(&path, DEFAULT_TTL);
+        assert_eq!(cache.get("root", "Reports"), None);
+        cache.insert("root", "Reports", "1rep").unwrap();
+        cache.insert("1rep", "a/b.txt", "1ab").unwrap();
+
+        let reopened = NameCache::open(&path, DEFAULT_TTL);
+        assert_eq!(reopened.get("root", "Reports").as_deref(), Some("1rep"));
+        assert_eq!(reopened.get("1rep", "a/b.txt").as_deref(), Some("1ab"));
+
+        reopened.remove("1rep", "a/b.txt").unwrap();
+        assert_eq!(reopened.clear().unwrap(), 1);
+        assert!(NameCache::open(&path, DEFAULT_TTL).is_empty());
+    }
+
+    #[test]
+    fn test_name_cache_expiry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("names.json");
+        let stale = unix_now() - 7200;
+        fs::write(
+            &path,
+            format!(r#"{{"root/old": {{"id": "1old", "cached_at": {}}}}}"#, stale),
+        )
+        .unwrap();
+
+        assert_eq!(NameCache::open(&path, DEFAULT_TTL).get("root", "old"), None);
+        let long_ttl = Duration::from_secs(3 * 3600);
+        assert_eq!(NameCache::open(&path, long_ttl).get("root", "old").as_deref(), Some("1old"));
+
+        let cache = NameCache::open(&path, Duration::ZERO);
+        cache.insert("root", "new", "1new").unwrap();
+        assert_eq!(cache.get("root", "new"), None);
+    }
+}
@@ -0,0 +1,542 @@
+//! Sign-in with a personal Google account.
+//!
+//! [`LoopbackLogin`] implements the OAuth2 flow for installed apps: it
+//! listens on a random port of `127.0.0.1`, the user approves access in a
+//! browser, and Google redirects back with an authorization code that is
+//! exchanged for a refresh token. The result is saved as an
+//! [`AuthorizedUser`] file that [`crate::Authenticator::from_file`] accepts
+//! in place of a service account key.
+//!
+//! On machines without a browser, [`DeviceLogin`] implements the device
+//! authorization grant instead: the user enters a short code on another
+//! device while the tool polls for the result. Google only allows a few
+//! scopes, such as `drive.file`, with this flow, and it needs an OAuth client
+//! of type "TVs and Limited Input devices".
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use crate::auth::{Scope, TOKEN_URI};
+use crate::client::DRIVE_API_BASE;
+use crate::config::config_home;
+use crate::error::{DriveError, Result};
+use crate::models::{AuthorizedUser, TokenResponse};
+
+/// Google OAuth2 authorization endpoint.
+const AUTH_URI: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+
+/// Google OAuth2 device authorization endpoint.
+const DEVICE_CODE_URI: &str = "https://oauth2.googleapis.com/device/code";
+
+/// Largest redirect request read from the browser.
+const MAX_REQUEST_SIZE: usize = 16 * 1024;
+
+/// OAuth client of a "Desktop app", as downloaded from the Cloud console.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClientSecrets {
+    pub client_id: String,
+    pub client_secret: String,
+    #[serde(default)]
+    pub auth_uri: Option<String>,
+    #[serde(default)]
+    pub token_uri: Option<String>,
+    /// Device authorization endpoint; not part of downloaded files.
+    #[serde(default)]
+    pub device_code_uri: Option<String>,
+}
+
+impl ClientSecrets {
+    /// Read a client secrets file, either as downloaded (with the client
+    /// under an `installed` or `web` key) or as a bare object.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content =
+            fs::read_to_string(path.as_ref()).map_err(DriveError::CredentialsFileError)?;
+        let mut value: serde_json::Value = serde_json::from_str(&content)?;
+        for key in ["installed", "web"] {
+            if let Some(inner) = value.get_mut(key) {
+                return Ok(serde_json::from_value(inner.take())?);
+            }
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+/// Default file for the credentials of the signed-in account:
+/// `$XDG_CONFIG_HOME/share_drive/authorized_user.json`, falling back to
+/// `~/.config/share_drive/authorized_user.json`.
+pub fn default_credentials_path() -> Option<PathBuf> {
+    Some(config_home()?.join("authorized_user.json"))
+}
+
+/// Write `user` to `path`, readable only by the current user on Unix.
+pub fn save_credentials(user: &AuthorizedUser, path: &Path) -> Result<()> {
+    let write_err = |e| DriveError::FileWriteError {
+        path: path.display().to_string(),
+        source: e,
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(write_err)?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let content = serde_json::to_string_pretty(user)?;
+    std::io::Write::write_all(&mut options.open(path).map_err(write_err)?, content.as_bytes())
+        .map_err(write_err)
+}
+
+/// An authorization-code sign-in waiting for the browser to redirect back.
+pub struct LoopbackLogin {
+    secrets: ClientSecrets,
+    listener: TcpListener,
+    redirect_uri: String,
+    /// Random value echoed back by Google, tying the redirect to this login.
+    state: String,
+    scope: Scope,
+    http: Client,
+    api_base: String,
+}
+
+impl LoopbackLogin {
+    /// Start listening for the redirect on a free local port. The account
+    /// will be asked to grant `scope`; Google is reached through `http`.
+    pub async fn start(secrets: ClientSecrets, scope: Scope, http: Client) -> Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .map_err(|e| DriveError::AuthenticationError(format!("cannot listen: {}", e)))?;
+        let port = listener
+            .local_addr()
+            .map_err(|e| DriveError::AuthenticationError(e.to_string()))?
+            .port();
+        Ok(Self {
+            secrets,
+            listener,
+            redirect_uri: format!("http://127.0.0.1:{}", port),
+            state: format!("{:032x}", rand::random::<u128>()),
+            scope,
+            http,
+            api_base: DRIVE_API_BASE.to_string(),
+        })
+    }
+
+    /// Look up the signed-in account at `api_base` instead of the Drive API.
+    pub fn with_api_base<S: Into<String>>(mut self, api_base: S) -> Self {
+        self.api_base = api_base.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Page the user has to open to grant access.
+    pub fn authorize_url(&self) -> String {
+        let base = self.secrets.auth_uri.as_deref().unwrap_or(AUTH_URI);
+        let params = [
+            ("client_id", self.secrets.client_id.as_str()),
+            ("redirect_uri", &self.redirect_uri),
+            ("response_type", "code"),
+            ("scope", self.scope.as_str()),
+            ("state", &self.state),
+            // Ask for a refresh token every time, not only on first consent
+            ("access_type", "offline"),
+            ("prompt", "consent"),
+        ];
+        match Url::parse_with_params(base, params) {
+            Ok(url) => url.to_string(),
+            Err(_) => base.to_string(),
+        }
+    }
+
+    /// Wait for the browser to come back and exchange the code for a
+    /// refresh token.
+    pub async fn finish(self) -> Result<AuthorizedUser> {
+        let code = self.receive_code().await?;
+        let (mut user, access_token) = self.exchange_code(&code).await?;
+        user.account = account_email(&self.http, &self.api_base, &access_token).await;
+        Ok(user)
+    }
+
+    /// Serve redirect requests until one carries a code or an error.
+    async fn receive_code(&self) -> Result<String> {
+        loop {
+            let (mut stream, _) = self
+                .listener
+                .accept()
+                .await
+                .map_err(|e| DriveError::AuthenticationError(e.to_string()))?;
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n")
+                && request.len() < MAX_REQUEST_SIZE
+            {
+                match stream.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+
+            // Browsers also ask for /favicon.ico and the like
+            let params = callback_params(&String::from_utf8_lossy(&request)).unwrap_or_default();
+            let result = if let Some(error) = params.get("error") {
+                Err(DriveError::AuthenticationError(format!("access denied: {}", error)))
+            } else if let Some(code) = params.get("code") {
+                match params.get("state") {
+                    Some(state) if *state == self.state => Ok(code.clone()),
+                    _ => Err(DriveError::AuthenticationError(
+                        "sign-in response does not belong to this login".to_string(),
+                    )),
+                }
+            } else {
+                let not_found = b"HTTP/1.1 404 Not Found\r\n\
+                    Content-Length: 0\r\nConnection: close\r\n\r\n";
+                let _ = stream.write_all(not_found).await;
+                continue;
+            };
+
+            let message = match result {
+                Ok(_) => "Signed in to share_drive. You can close this window.",
+                Err(_) => "Sign-in to share_drive failed. See the terminal for details.",
+            };
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                message.len(),
+                message
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+            return result;
+        }
+    }
+
+    /// Trade an authorization code for the account's tokens.
+    async fn exchange_code(&self, code: &str) -> Result<(AuthorizedUser, String)> {
+        let token_uri = self.secrets.token_uri.as_deref().unwrap_or(TOKEN_URI);
+        let params = [
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("client_id", &self.secrets.client_id),
+            ("client_secret", &self.secrets.client_secret),
+            ("redirect_uri", &self.redirect_uri),
+        ];
+        let response = self.http.post(token_uri).form(&params).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(DriveError::AuthenticationError(format!(
+                "Status {}: {}",
+                status, body
+            )));
+        }
+        authorized_user(&self.secrets, response.json().await?)
+    }
+}
+
+/// A device authorization sign-in waiting for the user to enter the code.
+pub struct DeviceLogin {
+    secrets: ClientSecrets,
+    http: Client,
+    api_base: String,
+    device_code: String,
+    user_code: String,
+    verification_url: String,
+    interval: Duration,
+    expires_at: Instant,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceCodeResponse {
+    device_code: String,
+    user_code: String,
+    #[serde(alias = "verification_uri")]
+    verification_url: String,
+    expires_in: u64,
+    #[serde(default = "default_poll_interval")]
+    interval: u64,
+}
+
+fn default_poll_interval() -> u64 {
+    5
+}
+
+impl DeviceLogin {
+    /// Ask Google, through `http`, for a user code to show, for access with
+    /// `scope`.
+    pub async fn start(secrets: ClientSecrets, scope: Scope, http: Client) -> Result<Self> {
+        let uri = secrets.device_code_uri.as_deref().unwrap_or(DEVICE_CODE_URI);
+        let params = [("client_id", secrets.client_id.as_str()), ("scope", scope.as_str())];
+        let response = http.post(uri).form(&params).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(DriveError::AuthenticationError(format!(
+                "Status {}: {}",
+                status, body
+            )));
+        }
+
+        let device: DeviceCodeResponse = response.json().await?;
+        Ok(Self {
+            secrets,
+            http,
+            api_base: DRIVE_API_BASE.to_string(),
+            device_code: device.device_code,
+            user_code: device.user_code,
+            verification_url: device.verification_url,
+            interval: Duration::from_secs(device.interval),
+            expires_at: Instant::now() + Duration::from_secs(device.expires_in),
+        })
+    }
+
+    /// Look up the signed-in account at `api_base` instead of the Drive API.
+    pub fn with_api_base<S: Into<String>>(mut self, api_base: S) -> Self {
+        self.api_base = api_base.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Code the user has to enter at [`Self::verification_url`].
+    pub fn user_code(&self) -> &str {
+        &self.user_code
+    }
+
+    /// Page where the user enters the code.
+    pub fn verification_url(&self) -> &str {
+        &self.verification_url
+    }
+
+    /// Poll until the user has approved or denied access, or the code expires.
+    pub async fn finish(mut self) -> Result<AuthorizedUser> {
+        let token_uri = self.secrets.token_uri.as_deref().unwrap_or(TOKEN_URI);
+        loop {
+            if Instant::now() >= self.expires_at {
+                return Err(DriveError::AuthenticationError(
+                    "the code expired before access was granted".to_string(),
+                ));
+            }
+            tokio::time::sleep(self.interval).await;
+
+            let params = [
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ("device_code", &self.device_code),
+                ("client_id", &self.secrets.client_id),
+                ("client_secret", &self.secrets.client_secret),
+            ];
+            let response = self.http.post(token_uri).form(&params).send().await?;
+            if response.status().is_success() {
+                let (mut user, access_token) =
+                    authorized_user(&self.secrets, response.json().await?)?;
+                user.account = account_email(&self.http, &self.api_base, &access_token).await;
+                return Ok(user);
+            }
+
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            let error = serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|v| v["error"].as_str().map(str::to_string));
+            match error.as_deref() {
+                Some("authorization_pending") => {}
+                Some("slow_down") => self.interval += Duration::from_secs(5),
+                Some("access_denied") => {
+                    return Err(DriveError::AuthenticationError("access denied".to_string()))
+                }
+                _ => {
+                    return Err(DriveError::AuthenticationError(format!(
+                        "Status {}: {}",
+                        status, body
+                    )))
+                }
+            }
+        }
+    }
+}
+
+/// Credentials to save from the token response of a completed sign-in,
+/// along with the access token that came with them.
+fn authorized_user(
+    secrets: &ClientSecrets,
+    token: TokenResponse,
+) -> Result<(AuthorizedUser, String)> {
+    let refresh_token = token.refresh_token.ok_or_else(|| {
+        DriveError::AuthenticationError("no refresh token was returned".to_string())
+    })?;
+    let user = AuthorizedUser {
+        kind: "authorized_user".to_string(),
+        client_id: secrets.client_id.clone(),
+        client_secret: secrets.client_secret.clone(),
+        refresh_token,
+        token_uri: secrets.token_uri.clone(),
+        account: None,
+        keyring: false,
+    };
+    Ok((user, token.access_token))
+}
+
+/// Email address of the account `access_token` belongs to.
+///
+/// Only used for display, so a failed lookup does not fail the sign-in.
+async fn account_email(http: &Client, api_base: &str, access_token: &str) -> Option<String> {
+    let response = http
+        .get(format!("{}/about", api_base))
+        .bearer_auth(access_token)
+        .query(&[("fields", "user(emailAddress)")])
+        .send()
+        .await
+        .ok()?;
+    let about: serde_json::Value = response.error_for_status().ok()?.json().await.ok()?;
+    about["user"]["emailAddress"].as_str().map(str::to_string)
+}
+
+/// Query parameters of the HTTP request starting with `request`, e.g.
+/// `GET /?code=...&state=... HTTP/1.1`.
+fn callback_params(request: &str) -> Option<HashMap<String, String>> {
+    let target = request.lines().next()?.split_whitespace().nth(1)?;
+    let url = Url::parse("http://127.0.0.1").ok()?.join(target).ok()?;
+    Some(url.query_pairs().into_owned().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secrets(token_uri: Option<String>) -> ClientSecrets {
+        ClientSecrets {
+            client_id: "123.apps.googleusercontent.com".to_string(),
+            client_secret: "secret".to_string(),
+            auth_uri: None,
+            token_uri,
+            device_code_uri: None,
+        }
+    }
+
+    #[test]
+    fn test_client_secrets_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let downloaded = dir.path().join("client_secret.json");
+        fs::write(
+            &downloaded,
+            r#"{"installed": {"client_id": "id1", "client_secret": "s1",
+                "redirect_uris": ["http://localhost"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(ClientSecrets::from_file(&downloaded).unwrap().client_id, "id1");
+
+        let bare = dir.path().join("bare.json");
+        fs::write(&bare, r#"{"client_id": "id2", "client_secret": "s2"}"#).unwrap();
+        assert_eq!(ClientSecrets::from_file(&bare).unwrap().client_secret, "s2");
+    }
+
+    #[test]
+    fn test_callback_params() {
+        let params = callback_params("GET /?state=abc&code=4%2F0Ab HTTP/1.1\r\nHost: x\r\n\r\n")
+            .unwrap();
+        assert_eq!(params["code"], "4/0Ab");
+        assert_eq!(params["state"], "abc");
+        assert!(callback_params("GET /favicon.ico HTTP/1.1\r\n").unwrap().is_empty());
+        assert!(callback_params("").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_loopback_login() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/token")
+            .match_body(mockito::Matcher::UrlEncoded("code".into(), "4/code".into()))
+            .with_body(
+                r#"{"access_token": "ya29.a", "token_type": "Bearer", "expires_in": 3599,
+                    "refresh_token": "1//r"}"#,
+            )
+            .create_async()
+            .await;
+
+        let secrets = secrets(Some(format!("{}/token", server.url())));
+        let login = LoopbackLogin::start(secrets, Scope::Full, Client::new()).await.unwrap();
+        let url = login.authorize_url();
+        assert!(url.starts_with(AUTH_URI));
+        assert!(url.contains("access_type=offline"));
+
+        // Play the browser: an unrelated request, then the redirect
+        let base = login.redirect_uri.clone();
+        let redirect = format!("{}/?code=4%2Fcode&state={}", base, login.state);
+        let browser = tokio::spawn(async move {
+            let http = Client::new();
+            let favicon = http.get(format!("{}/favicon.ico", base)).send();
+            assert_eq!(favicon.await.unwrap().status(), 404);
+            http.get(redirect).send().await.unwrap().text().await.unwrap()
+        });
+
+        let code = login.receive_code().await.unwrap();
+        assert!(browser.await.unwrap().starts_with("Signed in"));
+        let (user, access_token) = login.exchange_code(&code).await.unwrap();
+        assert_eq!(access_token, "ya29.a");
+        assert_eq!(user.refresh_token, "1//r");
+        assert_eq!(user.kind, "authorized_user");
+        mock.assert_async().await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/user.json");
+        save_credentials(&user, &path).unwrap();
+        let saved: AuthorizedUser = serde_json::from_str(&fs::read_to_string(&path).unwrap())
+            .unwrap();
+        assert_eq!(saved.refresh_token, "1//r");
+    }
+
+    #[tokio::test]
+    async fn test_device_login() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/device/code")
+            .match_body(mockito::Matcher::UrlEncoded(
+                "scope".into(),
+                Scope::File.as_str().into(),
+            ))
+            .with_body(
+                r#"{"device_code": "AH-1", "user_code": "GQVQ-JKEC", "expires_in": 1800,
+                    "interval": 0, "verification_url": "https://www.google.com/device"}"#,
+            )
+            .create_async()
+            .await;
+        let pending = server
+            .mock("POST", "/token")
+            .with_status(428)
+            .with_body(r#"{"error": "authorization_pending"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let granted = server
+            .mock("POST", "/token")
+            .match_body(mockito::Matcher::UrlEncoded("device_code".into(), "AH-1".into()))
+            .with_body(
+                r#"{"access_token": "ya29.d", "token_type": "Bearer", "expires_in": 3599,
+                    "refresh_token": "1//d"}"#,
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/about")
+            .match_query(mockito::Matcher::Any)
+            .match_header("authorization", "Bearer ya29.d")
+            .with_body(r#"{"user": {"emailAddress": "someone@example.com"}}"#)
+            .create_async()
+            .await;
+
+        let mut secrets = secrets(Some(format!("{}/token", server.url())));
+        secrets.device_code_uri = Some(format!("{}/device/code", server.url()));
+        let login = DeviceLogin::start(secrets, Scope::File, Client::new())
+            .await
+            .unwrap()
+            .with_api_base(server.url());
+        assert_eq!(login.user_code(), "GQVQ-JKEC");
+        assert_eq!(login.verification_url(), "https://www.google.com/device");
+
+        let user = login.finish().await.unwrap();
+        assert_eq!(user.refresh_token, "1//d");
+        assert_eq!(user.account.as_deref(), Some("someone@example.com"));
+        pending.assert_async().await;
+        granted.assert_async().await;
+    }
+}
@@ -0,0 +1,282 @@
+//! Batch-level progress aggregation across several transfers, and transfer
+//! events delivered as a [`Stream`] rather than through callbacks.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use futures::channel::mpsc::{self, UnboundedSender};
+use futures::stream::{self, Stream, StreamExt};
+
+use crate::client::{ProgressCallback, TransferProgress};
+use crate::error::DriveError;
+use crate::models::FileMetadata;
+
+/// Something that happened during a single upload or download, as yielded by
+/// [`SharedDriveClient::upload_events`](crate::SharedDriveClient::upload_events)
+/// and [`SharedDriveClient::download_events`](crate::SharedDriveClient::download_events).
+///
+/// A stream ends with exactly one of `Finished`, `Skipped` or `Failed`.
+#[derive(Debug)]
+pub enum TransferEvent {
+    /// Content started to move; `total_bytes` is 0 when unknown, as for exports.
+    Started { total_bytes: u64 },
+    /// More of the content was transferred.
+    Progress(TransferProgress),
+    /// A request of the transfer, such as one upload chunk, failed
+    /// transiently and is sent again; `attempt` counts from 2.
+    ChunkRetried { attempt: u64 },
+    /// The transfer completed.
+    Finished(FileMetadata),
+    /// Nothing was uploaded, as the destination already had this file.
+    Skipped(FileMetadata),
+    /// The transfer failed.
+    Failed(DriveError),
+}
+
+tokio::task_local! {
+    /// Where the transfer running in this task reports its events, if it
+    /// was started as an event stream.
+    static EVENTS: UnboundedSender<TransferEvent>;
+}
+
+/// Report `event` to the stream of the transfer running in this task, if any.
+pub(crate) fn emit(event: TransferEvent) {
+    let _ = EVENTS.try_with(|events| {
+        // A dropped stream no longer wants events
+        let _ = events.unbounded_send(event);
+    });
+}
+
+/// Run `transfer` as a stream of its events, ending with the one it returns.
+///
+/// `transfer` gets a progress callback that feeds the stream, and anything
+/// it passes to [`emit`] shows up in order. It only runs while the stream
+/// is polled and is cancelled when the stream is dropped.
+pub(crate) fn event_stream<'a, F, Fut>(transfer: F) -> impl Stream<Item = TransferEvent> + 'a
+where
+    F: FnOnce(ProgressCallback) -> Fut + 'a,
+    Fut: Future<Output = TransferEvent> + 'a,
+{
+    let (sender, receiver) = mpsc::unbounded();
+    let progress_sender = sender.clone();
+    let progress: ProgressCallback = Arc::new(move |p| {
+        let _ = progress_sender.unbounded_send(TransferEvent::Progress(p));
+    });
+    let events = sender.clone();
+    let run = EVENTS.scope(events, async move {
+        let last = transfer(progress).await;
+        let _ = sender.unbounded_send(last);
+    });
+    // The receiver ends once the transfer, and with it every sender, is done
+    let driver = stream::once(run).filter_map(|()| async { None });
+    stream::select(receiver, driver)
+}
+
+/// Combines per-file progress of a batch into overall progress.
+///
+/// Create one with the sizes of all files in the batch, hand each transfer the
+/// callback returned by [`BatchProgress::file_callback`], and the batch
+/// callback receives a [`TransferProgress`] covering the whole batch: total
+/// bytes moved, overall speed, and ETA until the batch finishes.
+#[derive(Clone)]
+pub struct BatchProgress {
+    inner: Arc<BatchInner>,
+}
+
+struct BatchInner {
+    callback: ProgressCallback,
+    start_time: Instant,
+    state: Mutex<BatchState>,
+}
+
+struct BatchState {
+    sizes: Vec<u64>,
+    transferred: Vec<u64>,
+}
+
+impl BatchState {
+    fn progress(&self, start_time: Instant) -> TransferProgress {
+        let bytes_transferred: u64 = self.transferred.iter().sum();
+        let elapsed = start_time.elapsed().as_secs_f64();
+        let bytes_per_second = if elapsed > 0.0 {
+            bytes_transferred as f64 / elapsed
+        } else {
+            0.0
+        };
+
+        TransferProgress {
+            bytes_transferred,
+            total_bytes: self.sizes.iter().sum(),
+            bytes_per_second,
+        }
+    }
+}
+
+impl BatchProgress {
+    /// Create an aggregator for files of the given sizes.
+    pub fn new(file_sizes: Vec<u64>, callback: ProgressCallback) -> Self {
+        let transferred = vec![0; file_sizes.len()];
+        Self {
+            inner: Arc::new(BatchInner {
+                callback,
+                start_time: Instant::now(),
+                state: Mutex::new(BatchState {
+                    sizes: file_sizes,
+                    transferred,
+                }),
+            }),
+        }
+    }
+
+    /// Current progress of the whole batch.
+    pub fn snapshot(&self) -> TransferProgress {
+        self.state().progress(self.inner.start_time)
+    }
+
+    /// Progress callback for the file at `index`.
+    ///
+    /// Updates are folded into the batch total and then forwarded to `file`,
+    /// if given, so per-file and batch displays can be driven together.
+    pub fn file_callback(&self, index: usize, file: Option<ProgressCallback>) -> ProgressCallback {
+        let batch = self.clone();
+        Arc::new(move |p: TransferProgress| {
+            batch.update(|state| {
+                if let Some(slot) = state.transferred.get_mut(index) {
+                    *slot = p.bytes_transferred;
+                }
+            });
+            if let Some(ref file) = file {
+                file(p);
+            }
+        })
+    }
+
+    /// Mark the file at `index` as fully transferred.
+    ///
+    /// Needed for transfers that finish without reporting progress, such as
+    /// small single-request uploads.
+    pub fn finish_file(&self, index: usize) {
+        self.update(|state| {
+            if index < state.sizes.len() {
+                state.transferred[index] = state.sizes[index];
+            }
+        });
+    }
+
+    /// Remove the file at `index` from the batch (skipped or failed), so it
+    /// neither counts towards the total nor skews the speed estimate.
+    pub fn skip_file(&self, index: usize) {
+        self.update(|state| {
+            if index < state.sizes.len() {
+                state.sizes[index] = 0;
+                state.transferred[index] = 0;
+            }
+        });
+    }
+
+    fn update(&self, change: impl FnOnce(&mut BatchState)) {
+        let progress = {
+            let mut state = self.state();
+            change(&mut state);
+            state.progress(self.inner.start_time)
+        };
+        (self.inner.callback)(progress);
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, BatchState> {
+        self.inner.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_event_stream_order() {
+        let events: Vec<_> = event_stream(|progress| async move {
+            emit(TransferEvent::Started { total_bytes: 10 });
+            progress(TransferProgress {
+                bytes_transferred: 10,
+                total_bytes: 10,
+                bytes_per_second: 0.0,
+            });
+            TransferEvent::Failed(DriveError::FileNotFound("a.txt".into()))
+        })
+        .collect()
+        .await;
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[0], TransferEvent::Started { total_bytes: 10 }));
+        assert!(matches!(events[1], TransferEvent::Progress(ref p) if p.bytes_transferred == 10));
+        assert!(matches!(events[2], TransferEvent::Failed(DriveError::FileNotFound(_))));
+
+        // Outside an event stream, events go nowhere
+        emit(TransferEvent::ChunkRetried { attempt: 2 });
+    }
+
+    fn recording_batch(sizes: Vec<u64>) -> (BatchProgress, Arc<Mutex<Vec<TransferProgress>>>) {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let batch = BatchProgress::new(
+            sizes,
+            Arc::new(move |p: TransferProgress| sink.lock().unwrap().push(p)),
+        );
+        (batch, seen)
+    }
+
+    #[test]
+    fn test_aggregates_file_progress() {
+        let (batch, seen) = recording_batch(vec![100, 300]);
+
+        batch.file_callback(0, None)(TransferProgress {
+            bytes_transferred: 50,
+            total_bytes: 100,
+            bytes_per_second: 0.0,
+        });
+        batch.file_callback(1, None)(TransferProgress {
+            bytes_transferred: 150,
+            total_bytes: 300,
+            bytes_per_second: 0.0,
+        });
+
+        let last = seen.lock().unwrap().last().cloned().unwrap();
+        assert_eq!(last.bytes_transferred, 200);
+        assert_eq!(last.total_bytes, 400);
+        assert_eq!(last.percent(), 50.0);
+    }
+
+    #[test]
+    fn test_finish_and_skip_file() {
+        let (batch, _) = recording_batch(vec![100, 300, 600]);
+
+        batch.finish_file(0);
+        batch.skip_file(2);
+
+        let snapshot = batch.snapshot();
+        assert_eq!(snapshot.bytes_transferred, 100);
+        assert_eq!(snapshot.total_bytes, 400);
+    }
+
+    #[test]
+    fn test_forwards_to_file_callback() {
+        let (batch, _) = recording_batch(vec![10]);
+        let forwarded = Arc::new(Mutex::new(0u64));
+        let sink = forwarded.clone();
+
+        let callback = batch.file_callback(
+            0,
+            Some(Arc::new(move |p: TransferProgress| {
+                *sink.lock().unwrap() = p.bytes_transferred
+            })),
+        );
+        callback(TransferProgress {
+            bytes_transferred: 7,
+            total_bytes: 10,
+            bytes_per_second: 0.0,
+        });
+
+        assert_eq!(*forwarded.lock().unwrap(), 7);
+    }
+}
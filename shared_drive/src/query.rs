@@ -0,0 +1,617 @@
+//! Building Drive `q` query strings from typed filters.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::LazyLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use regex::Regex;
+
+use crate::error::{DriveError, Result};
+use crate::models::{FileMetadata, FOLDER_MIME_TYPE};
+
+/// `YYYY-MM-DD`, optionally followed by a time and UTC offset.
+static TIMESTAMP_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\d{4}-\d{2}-\d{2}(T\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:\d{2})?)?$")
+        .expect("Invalid timestamp regex")
+});
+
+/// A Drive search query that renders to a correctly escaped `q` string.
+///
+/// Build terms with the constructors and combine them with [`and`](Self::and),
+/// [`or`](Self::or) and [`not`](Self::not); the [`Display`](fmt::Display)
+/// output is ready to pass to
+/// [`SharedDriveClient::query_files`](crate::SharedDriveClient::query_files).
+///
+/// ```
+/// use share_drive::DriveQuery;
+///
+/// let query = DriveQuery::in_parent("folder123")
+///     .and(DriveQuery::name_contains("report").or(DriveQuery::name_contains("summary")))
+///     .and(DriveQuery::trashed(false));
+/// assert_eq!(
+///     query.to_string(),
+///     "'folder123' in parents and (name contains 'report' or name contains 'summary') \
+///      and trashed = false"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DriveQuery {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Term(String),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+}
+
+impl DriveQuery {
+    fn term(term: String) -> Self {
+        Self {
+            expr: Expr::Term(term),
+        }
+    }
+
+    /// Name equals `name` exactly.
+    pub fn name_is(name: &str) -> Self {
+        Self::term(format!("name = '{}'", escape_query_value(name)))
+    }
+
+    /// Name contains `text`. Drive matches on word prefixes, case-insensitively.
+    pub fn name_contains(text: &str) -> Self {
+        Self::term(format!("name contains '{}'", escape_query_value(text)))
+    }
+
+    /// Name, description or indexed content contains `text`.
+    pub fn full_text_contains(text: &str) -> Self {
+        Self::term(format!("fullText contains '{}'", escape_query_value(text)))
+    }
+
+    /// MIME type equals `mime_type`.
+    pub fn mime_type_is(mime_type: &str) -> Self {
+        Self::term(format!("mimeType = '{}'", escape_query_value(mime_type)))
+    }
+
+    /// Folders only.
+    pub fn folders() -> Self {
+        Self::mime_type_is(FOLDER_MIME_TYPE)
+    }
+
+    /// Everything except folders.
+    pub fn files() -> Self {
+        Self::term(format!("mimeType != '{}'", FOLDER_MIME_TYPE))
+    }
+
+    /// Direct children of the folder `parent_id`.
+    pub fn in_parent(parent_id: &str) -> Self {
+        Self::term(format!("'{}' in parents", escape_query_value(parent_id)))
+    }
+
+    /// Whether the file is in the trash.
+    pub fn trashed(trashed: bool) -> Self {
+        Self::term(format!("trashed = {}", trashed))
+    }
+
+    /// Whether the authenticated account has starred the file.
+    pub fn starred(starred: bool) -> Self {
+        Self::term(format!("starred = {}", starred))
+    }
+
+    /// Modified strictly after the RFC 3339 `timestamp` (see [`parse_timestamp`]).
+    pub fn modified_after(timestamp: &str) -> Self {
+        Self::term(format!("modifiedTime > '{}'", escape_query_value(timestamp)))
+    }
+
+    /// Modified strictly before the RFC 3339 `timestamp`.
+    pub fn modified_before(timestamp: &str) -> Self {
+        Self::term(format!("modifiedTime < '{}'", escape_query_value(timestamp)))
+    }
+
+    /// Created strictly after the RFC 3339 `timestamp`.
+    pub fn created_after(timestamp: &str) -> Self {
+        Self::term(format!("createdTime > '{}'", escape_query_value(timestamp)))
+    }
+
+    /// Created strictly before the RFC 3339 `timestamp`.
+    pub fn created_before(timestamp: &str) -> Self {
+        Self::term(format!("createdTime < '{}'", escape_query_value(timestamp)))
+    }
+
+    /// Has the custom application property `key` set to `value`.
+    pub fn app_property(key: &str, value: &str) -> Self {
+        Self::term(format!(
+            "appProperties has {{ key='{}' and value='{}' }}",
+            escape_query_value(key),
+            escape_query_value(value)
+        ))
+    }
+
+    /// Both this query and `other` must match.
+    pub fn and(self, other: DriveQuery) -> Self {
+        let mut terms = match self.expr {
+            Expr::And(terms) => terms,
+            expr => vec![expr],
+        };
+        match other.expr {
+            Expr::And(more) => terms.extend(more),
+            expr => terms.push(expr),
+        }
+        Self {
+            expr: Expr::And(terms),
+        }
+    }
+
+    /// This query or `other` must match.
+    pub fn or(self, other: DriveQuery) -> Self {
+        let mut terms = match self.expr {
+            Expr::Or(terms) => terms,
+            expr => vec![expr],
+        };
+        match other.expr {
+            Expr::Or(more) => terms.extend(more),
+            expr => terms.push(expr),
+        }
+        Self {
+            expr: Expr::Or(terms),
+        }
+    }
+
+    /// Negate this query.
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        Self {
+            expr: Expr::Not(Box::new(self.expr)),
+        }
+    }
+
+    /// Combine queries with `and`; `None` when `queries` is empty.
+    pub fn all(queries: impl IntoIterator<Item = DriveQuery>) -> Option<Self> {
+        queries.into_iter().reduce(DriveQuery::and)
+    }
+
+    /// Combine queries with `or`; `None` when `queries` is empty.
+    pub fn any(queries: impl IntoIterator<Item = DriveQuery>) -> Option<Self> {
+        queries.into_iter().reduce(DriveQuery::or)
+    }
+}
+
+impl fmt::Display for DriveQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.expr.fmt(f)
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Nested groups of the other operator are parenthesized
+        let join = |f: &mut fmt::Formatter<'_>, terms: &[Expr], op: &str| {
+            for (i, term) in terms.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {} ", op)?;
+                }
+                match term {
+                    Expr::And(_) | Expr::Or(_) => write!(f, "({})", term)?,
+                    _ => write!(f, "{}", term)?,
+                }
+            }
+            Ok(())
+        };
+        match self {
+            Expr::Term(term) => f.write_str(term),
+            Expr::And(terms) => join(f, terms, "and"),
+            Expr::Or(terms) => join(f, terms, "or"),
+            Expr::Not(inner) => write!(f, "not ({})", inner),
+        }
+    }
+}
+
+/// Whether to list regular files, folders, or both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    /// Anything that is not a folder.
+    File,
+    /// Folders only.
+    Folder,
+}
+
+/// Whether to list entries that are in the trash.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrashFilter {
+    /// Leave trashed entries out.
+    #[default]
+    Exclude,
+    /// List trashed entries along with the rest.
+    Include,
+    /// List only trashed entries.
+    Only,
+}
+
+impl TrashFilter {
+    /// Drive query clause for the filter, `None` for [`TrashFilter::Include`].
+    pub fn query(self) -> Option<DriveQuery> {
+        match self {
+            TrashFilter::Exclude => Some(DriveQuery::trashed(false)),
+            TrashFilter::Include => None,
+            TrashFilter::Only => Some(DriveQuery::trashed(true)),
+        }
+    }
+
+    /// Whether `file` passes the filter.
+    pub fn matches(self, file: &FileMetadata) -> bool {
+        match self {
+            TrashFilter::Exclude => !file.trashed,
+            TrashFilter::Include => true,
+            TrashFilter::Only => file.trashed,
+        }
+    }
+}
+
+/// Filters applied when listing or querying files.
+///
+/// Kind, MIME type and time bounds become clauses of the Drive query.
+/// The Drive API cannot filter on size, so size bounds are applied to the
+/// results client-side. [`FileFilter::matches`] evaluates the whole filter
+/// locally, for entries that were not fetched with the query clauses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileFilter {
+    /// Restrict results to files or folders.
+    pub kind: Option<FileKind>,
+    /// Exact MIME type to match.
+    pub mime_type: Option<String>,
+    /// Minimum size in bytes. Entries without a size (folders, Google-native files) are excluded.
+    pub min_size: Option<u64>,
+    /// Maximum size in bytes. Entries without a size are excluded.
+    pub max_size: Option<u64>,
+    /// Only entries modified strictly after this RFC 3339 timestamp.
+    pub modified_after: Option<String>,
+    /// Only entries modified strictly before this RFC 3339 timestamp.
+    pub modified_before: Option<String>,
+    /// Only entries with all of these `appProperties` set to the given values.
+    pub app_properties: BTreeMap<String, String>,
+    /// Only entries the authenticated account has starred.
+    pub starred: bool,
+}
+
+impl FileFilter {
+    /// Whether the filter does not restrict anything.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Drive query for the server-side part of the filter, `None` if there is none.
+    pub fn query(&self) -> Option<DriveQuery> {
+        let kind = self.kind.map(|kind| match kind {
+            FileKind::Folder => DriveQuery::folders(),
+            FileKind::File => DriveQuery::files(),
+        });
+        DriveQuery::all(
+            kind.into_iter()
+                .chain(self.starred.then(|| DriveQuery::starred(true)))
+                .chain(self.mime_type.as_deref().map(DriveQuery::mime_type_is))
+                .chain(self.modified_after.as_deref().map(DriveQuery::modified_after))
+                .chain(self.modified_before.as_deref().map(DriveQuery::modified_before))
+                .chain(self.app_properties.iter().map(|(k, v)| DriveQuery::app_property(k, v))),
+        )
+    }
+
+    /// Whether `file` passes every part of the filter, evaluated locally.
+    pub fn matches(&self, file: &FileMetadata) -> bool {
+        let kind_ok = match self.kind {
+            Some(FileKind::Folder) => file.is_folder(),
+            Some(FileKind::File) => !file.is_folder(),
+            None => true,
+        };
+        let mime_ok = self
+            .mime_type
+            .as_deref()
+            .is_none_or(|mime| file.mime_type.as_deref() == Some(mime));
+        let properties_ok = self
+            .app_properties
+            .iter()
+            .all(|(key, value)| file.app_properties.get(key) == Some(value));
+        kind_ok
+            && mime_ok
+            && properties_ok
+            && (!self.starred || file.starred)
+            && self.matches_size(file)
+            && self.matches_modified(file)
+    }
+
+    /// Whether `file` is within the size bounds, the part the Drive query cannot express.
+    pub fn matches_size(&self, file: &FileMetadata) -> bool {
+        if self.min_size.is_none() && self.max_size.is_none() {
+            return true;
+        }
+        let Some(size) = file.size else {
+            return false;
+        };
+        self.min_size.is_none_or(|min| size >= min) && self.max_size.is_none_or(|max| size <= max)
+    }
+
+    fn matches_modified(&self, file: &FileMetadata) -> bool {
+        if self.modified_after.is_none() && self.modified_before.is_none() {
+            return true;
+        }
+        let Some(modified) = file.modified_time.as_deref().and_then(timestamp_seconds) else {
+            return false;
+        };
+        let bound = |t: &Option<String>| t.as_deref().and_then(timestamp_seconds);
+        bound(&self.modified_after).is_none_or(|after| modified > after)
+            && bound(&self.modified_before).is_none_or(|before| modified < before)
+    }
+}
+
+/// Escape a value for use inside a single-quoted Drive query string.
+///
+/// Backslashes and single quotes are the only characters Drive treats
+/// specially there. Prefer [`DriveQuery`], which escapes every value it is
+/// given; use this when writing a `q` string by hand.
+///
+/// ```
+/// use share_drive::escape_query_value;
+///
+/// let query = format!("name = '{}'", escape_query_value(r"Bob's \ notes"));
+/// assert_eq!(query, r"name = 'Bob\'s \\ notes'");
+/// ```
+pub fn escape_query_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+/// Validate a date (`2024-01-01`) or RFC 3339 timestamp for use in a query.
+///
+/// Plain dates are expanded to midnight UTC.
+pub fn parse_timestamp(input: &str) -> Result<String> {
+    let input = input.trim();
+    if !TIMESTAMP_REGEX.is_match(input) {
+        return Err(DriveError::InvalidQuery(format!(
+            "invalid date '{}', expected YYYY-MM-DD or RFC 3339",
+            input
+        )));
+    }
+    if input.len() == 10 {
+        Ok(format!("{}T00:00:00Z", input))
+    } else {
+        Ok(input.to_string())
+    }
+}
+
+/// Seconds since the Unix epoch for a date or RFC 3339 timestamp, ignoring fractions.
+pub(crate) fn timestamp_seconds(input: &str) -> Option<i64> {
+    if !TIMESTAMP_REGEX.is_match(input) {
+        return None;
+    }
+    let num = |range: std::ops::Range<usize>| input.get(range)?.parse::<i64>().ok();
+    let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    let (hour, minute, second) = if input.len() > 10 {
+        (num(11..13)?, num(14..16)?, num(17..19)?)
+    } else {
+        (0, 0, 0)
+    };
+    let offset = match input.rfind(['+', '-']).filter(|&i| i > 18) {
+        Some(i) => {
+            let sign = if &input[i..=i] == "-" { -1 } else { 1 };
+            sign * (num(i + 1..i + 3)? * 3600 + num(i + 4..i + 6)? * 60)
+        }
+        None => 0,
+    };
+
+    // Days from civil date (Howard Hinnant's algorithm).
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    Some(days * 86_400 + hour * 3600 + minute * 60 + second - offset)
+}
+
+/// A date or RFC 3339 timestamp as a [`SystemTime`], ignoring fractions.
+pub(crate) fn timestamp_to_system_time(input: &str) -> Option<SystemTime> {
+    let seconds = timestamp_seconds(input)?;
+    let offset = Duration::from_secs(seconds.unsigned_abs());
+    if seconds >= 0 {
+        UNIX_EPOCH.checked_add(offset)
+    } else {
+        UNIX_EPOCH.checked_sub(offset)
+    }
+}
+
+/// Format a [`SystemTime`] as an RFC 3339 UTC timestamp with milliseconds,
+/// the form Drive uses for `modifiedTime`.
+pub fn format_timestamp(time: SystemTime) -> String {
+    let time = time::OffsetDateTime::from(time);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        time.year(),
+        u8::from(time.month()),
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second(),
+        time.millisecond()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_of_size(size: Option<u64>) -> FileMetadata {
+        FileMetadata {
+            id: "f".to_string(),
+            name: "f".to_string(),
+            size,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_filter_query() {
+        let filter = FileFilter {
+            kind: Some(FileKind::File),
+            mime_type: Some("text/plain".to_string()),
+            modified_after: Some("2024-01-01T00:00:00Z".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            filter.query().unwrap().to_string(),
+            format!(
+                "mimeType != '{}' and mimeType = 'text/plain' \
+                 and modifiedTime > '2024-01-01T00:00:00Z'",
+                FOLDER_MIME_TYPE
+            )
+        );
+        assert!(FileFilter::default().query().is_none());
+    }
+
+    #[test]
+    fn test_drive_query_escapes_values() {
+        let query = DriveQuery::name_is("Bob's \\ file").and(DriveQuery::in_parent("p'1"));
+        assert_eq!(
+            query.to_string(),
+            r"name = 'Bob\'s \\ file' and 'p\'1' in parents"
+        );
+    }
+
+    #[test]
+    fn test_drive_query_composition() {
+        let a = DriveQuery::name_contains("a");
+        let b = DriveQuery::name_contains("b");
+        let c = DriveQuery::mime_type_is("text/plain");
+
+        assert_eq!(
+            a.clone().or(b.clone()).and(c.clone()).to_string(),
+            "(name contains 'a' or name contains 'b') and mimeType = 'text/plain'"
+        );
+        assert_eq!(
+            a.clone().and(b.clone()).or(c.clone()).to_string(),
+            "(name contains 'a' and name contains 'b') or mimeType = 'text/plain'"
+        );
+        assert_eq!(
+            DriveQuery::trashed(true).not().to_string(),
+            "not (trashed = true)"
+        );
+        assert_eq!(
+            DriveQuery::any([a, b, c]).unwrap().to_string(),
+            "name contains 'a' or name contains 'b' or mimeType = 'text/plain'"
+        );
+        assert!(DriveQuery::all([]).is_none());
+    }
+
+    #[test]
+    fn test_escape_query_value() {
+        assert_eq!(escape_query_value("it's"), "it\\'s");
+        assert_eq!(escape_query_value(r"a\b"), r"a\\b");
+        assert_eq!(escape_query_value(r"x\' or 1"), r"x\\\' or 1");
+    }
+
+    #[test]
+    fn test_size_matching() {
+        let filter = FileFilter {
+            min_size: Some(10),
+            max_size: Some(100),
+            ..Default::default()
+        };
+        assert!(filter.matches_size(&file_of_size(Some(10))));
+        assert!(filter.matches_size(&file_of_size(Some(100))));
+        assert!(!filter.matches_size(&file_of_size(Some(9))));
+        assert!(!filter.matches_size(&file_of_size(Some(101))));
+        assert!(!filter.matches_size(&file_of_size(None)));
+        assert!(FileFilter::default().matches_size(&file_of_size(None)));
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("2024-01-01").unwrap(), "2024-01-01T00:00:00Z");
+        assert_eq!(
+            parse_timestamp("2024-06-01T12:30:00+02:00").unwrap(),
+            "2024-06-01T12:30:00+02:00"
+        );
+        assert!(parse_timestamp("yesterday").is_err());
+        assert!(parse_timestamp("2024-01-01' or '1").is_err());
+    }
+
+    #[test]
+    fn test_timestamp_seconds() {
+        assert_eq!(timestamp_seconds("1970-01-01"), Some(0));
+        assert_eq!(timestamp_seconds("2024-01-01T00:00:00Z"), Some(1_704_067_200));
+        assert_eq!(timestamp_seconds("2024-01-01T00:00:00.123Z"), Some(1_704_067_200));
+        assert_eq!(timestamp_seconds("2024-01-01T02:00:00+02:00"), Some(1_704_067_200));
+        assert_eq!(timestamp_seconds("2023-12-31T19:00:00-05:00"), Some(1_704_067_200));
+        assert_eq!(timestamp_seconds("not a date"), None);
+    }
+
+    #[test]
+    fn test_format_timestamp_round_trip() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_704_067_200_123);
+        let formatted = format_timestamp(time);
+        assert_eq!(formatted, "2024-01-01T00:00:00.123Z");
+        assert_eq!(
+            timestamp_to_system_time(&formatted),
+            Some(UNIX_EPOCH + Duration::from_secs(1_704_067_200))
+        );
+    }
+
+    #[test]
+    fn test_matches_whole_filter() {
+        let mut file = file_of_size(Some(50));
+        file.mime_type = Some("text/plain".to_string());
+        file.modified_time = Some("2024-03-01T10:00:00.000Z".to_string());
+
+        let filter = FileFilter {
+            kind: Some(FileKind::File),
+            mime_type: Some("text/plain".to_string()),
+            modified_after: Some("2024-01-01T00:00:00Z".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&file));
+
+        let folders = FileFilter {
+            kind: Some(FileKind::Folder),
+            ..Default::default()
+        };
+        assert!(!folders.matches(&file));
+
+        let later = FileFilter {
+            modified_after: Some("2024-06-01T00:00:00Z".to_string()),
+            ..Default::default()
+        };
+        assert!(!later.matches(&file));
+    }
+
+    #[test]
+    fn test_app_property_filter() {
+        let filter = FileFilter {
+            app_properties: BTreeMap::from([("build".to_string(), "12'34".to_string())]),
+            ..Default::default()
+        };
+        assert_eq!(
+            filter.query().unwrap().to_string(),
+            r"appProperties has { key='build' and value='12\'34' }"
+        );
+
+        let mut file = file_of_size(Some(1));
+        assert!(!filter.matches(&file));
+        file.app_properties.insert("build".to_string(), "12'34".to_string());
+        assert!(filter.matches(&file));
+    }
+
+    #[test]
+    fn test_starred_filter() {
+        let filter = FileFilter {
+            starred: true,
+            ..Default::default()
+        };
+        assert_eq!(filter.query().unwrap().to_string(), "starred = true");
+
+        let mut file = file_of_size(Some(1));
+        assert!(!filter.matches(&file));
+        file.starred = true;
+        assert!(filter.matches(&file));
+    }
+}
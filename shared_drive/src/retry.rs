@@ -0,0 +1,238 @@
+//! Retry with exponential backoff for transient Drive API failures.
+
+use std::future::Future;
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::Response;
+
+use crate::error::{DriveError, Result};
+use crate::models::ApiErrorResponse;
+
+/// Error reasons Google uses on 403 responses to signal throttling.
+const RATE_LIMIT_REASONS: &[&str] = &["userRateLimitExceeded", "rateLimitExceeded"];
+
+/// How failed requests are retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one. `1` disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled for every further attempt.
+    pub base_delay: Duration,
+    /// Upper bound for a single delay.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(32),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that performs a single attempt.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Exponential delay ceiling before retry number `retry` (0-based), without jitter.
+    pub fn max_backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry);
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// Delay before retry number `retry` (0-based): half the exponential
+    /// ceiling plus a random share of the other half.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let ceiling = self.max_backoff(retry);
+        let half = ceiling / 2;
+        let jitter_ms = rand::rng().random_range(0..=half.as_millis() as u64);
+        half + Duration::from_millis(jitter_ms)
+    }
+}
+
+/// Whether an HTTP status indicates a transient server-side failure.
+pub fn is_retryable_status(status: u16) -> bool {
+    matches!(status, 408 | 500 | 502 | 503 | 504)
+}
+
+/// Whether a 403 error body carries a rate-limit reason.
+pub fn is_rate_limit_body(body: &str) -> bool {
+    serde_json::from_str::<ApiErrorResponse>(body)
+        .map(|e| {
+            e.error
+                .errors
+                .iter()
+                .any(|item| RATE_LIMIT_REASONS.contains(&item.reason.as_str()))
+        })
+        .unwrap_or(false)
+}
+
+/// Whether a response with `status` and `body` is worth retrying: a
+/// transient server failure or a rate limit.
+pub fn is_retryable_response(status: u16, body: &str) -> bool {
+    is_retryable_status(status) || status == 429 || (status == 403 && is_rate_limit_body(body))
+}
+
+/// Parse a `Retry-After` header given in seconds.
+pub fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Whether a request error is a transient network failure.
+fn is_retryable_error(err: &DriveError) -> bool {
+    match err {
+        DriveError::HttpError(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+        _ => false,
+    }
+}
+
+/// Run `send` until it yields a non-transient result or the policy gives up.
+///
+/// `send` must build and send a fresh request on every call. Responses with a
+/// retryable status are retried; once attempts are exhausted the last
+/// response is returned so the caller can turn it into an error as usual.
+///
+/// Throttling responses (429, or 403 with a rate-limit reason) wait for the
+/// server's `Retry-After` when given and otherwise back off; if they persist
+/// the result is [`DriveError::RateLimited`]. Other 403 responses are turned
+/// into [`DriveError::ApiError`] since their body has already been read.
+pub(crate) async fn send_with_retry<F, Fut>(policy: &RetryPolicy, mut send: F) -> Result<Response>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Response>>,
+{
+    let mut retry = 0;
+    loop {
+        let last_attempt = retry + 1 >= policy.max_attempts;
+        let response = match send().await {
+            Ok(response) => response,
+            Err(err) if !last_attempt && is_retryable_error(&err) => {
+                let delay = policy.backoff(retry);
+                tracing::info!(
+                    attempt = retry + 1,
+                    error = %err,
+                    delay_ms = delay.as_millis() as u64,
+                    "request failed, retrying"
+                );
+                tokio::time::sleep(delay).await;
+                retry += 1;
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+
+        let status = response.status().as_u16();
+        let delay = if status == 429 || status == 403 {
+            let retry_after = parse_retry_after(response.headers());
+            let body = response.text().await.unwrap_or_default();
+            if status == 403 && !is_rate_limit_body(&body) {
+                return Err(DriveError::from_response_body(status, &body));
+            }
+            if last_attempt {
+                let message = match DriveError::from_response_body(status, &body) {
+                    DriveError::ApiError { message, .. } => message,
+                    other => other.to_string(),
+                };
+                tracing::warn!(attempts = retry + 1, status, "still rate limited, giving up");
+                return Err(DriveError::RateLimited {
+                    retry_after,
+                    message,
+                });
+            }
+            retry_after.unwrap_or_else(|| policy.backoff(retry))
+        } else if !last_attempt && is_retryable_status(status) {
+            policy.backoff(retry)
+        } else {
+            return Ok(response);
+        };
+
+        tracing::info!(
+            attempt = retry + 1,
+            status,
+            delay_ms = delay.as_millis() as u64,
+            "transient response, retrying"
+        );
+        tokio::time::sleep(delay).await;
+        retry += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_backoff_doubles_and_caps() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+        };
+
+        assert_eq!(policy.max_backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.max_backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.max_backoff(3), Duration::from_millis(800));
+        assert_eq!(policy.max_backoff(4), Duration::from_secs(1));
+        assert_eq!(policy.max_backoff(40), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_backoff_within_jitter_bounds() {
+        let policy = RetryPolicy::default();
+        for retry in 0..6 {
+            let ceiling = policy.max_backoff(retry);
+            let delay = policy.backoff(retry);
+            assert!(delay >= ceiling / 2 && delay <= ceiling);
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_body() {
+        let body = r#"{"error": {"code": 403, "message": "User Rate Limit Exceeded",
+            "errors": [{"reason": "userRateLimitExceeded", "message": "slow down"}]}}"#;
+        assert!(is_rate_limit_body(body));
+
+        let body = r#"{"error": {"code": 403, "message": "Forbidden",
+            "errors": [{"reason": "insufficientFilePermissions"}]}}"#;
+        assert!(!is_rate_limit_body(body));
+        assert!(!is_rate_limit_body("not json"));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(7)));
+
+        headers.insert(RETRY_AFTER, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+
+    #[test]
+    fn test_retryable_status() {
+        for status in [408, 500, 502, 503, 504] {
+            assert!(is_retryable_status(status));
+        }
+        for status in [200, 308, 400, 401, 404] {
+            assert!(!is_retryable_status(status));
+        }
+    }
+}
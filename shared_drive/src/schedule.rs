@@ -0,0 +1,217 @@
+//! Cron-like schedules for recurring jobs.
+//!
+//! A schedule has the five classic cron fields, evaluated in UTC:
+//!
+//! ```text
+//! minute (0-59)  hour (0-23)  day of month (1-31)  month (1-12)  day of week (0-7)
+//! ```
+//!
+//! Each field accepts `*`, a value, a range `a-b`, a step `*/n` or `a-b/n`,
+//! and comma-separated lists of these. Days of the week start at Sunday,
+//! which can be given as 0 or 7. The shorthands `@hourly`, `@daily`,
+//! `@weekly` and `@monthly` are also accepted.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use time::{Date, OffsetDateTime, Time};
+
+use crate::error::{DriveError, Result};
+
+/// Days searched for the next matching time before giving up (covers leap days).
+const SEARCH_DAYS: u32 = 5 * 366;
+
+/// A parsed cron expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Whether the day-of-month and day-of-week fields were both restricted,
+    /// in which case a day matches if either does (as in cron).
+    either_day: bool,
+}
+
+impl Schedule {
+    /// Parse a cron expression such as `*/15 * * * *` or `@daily`.
+    pub fn parse(expression: &str) -> Result<Self> {
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(DriveError::InvalidSchedule(format!(
+                "'{}' must have 5 fields (minute hour day month weekday)",
+                expression
+            )));
+        };
+
+        let field = |value: &str, min: u32, max: u32| {
+            parse_field(value, min, max).map_err(|reason| {
+                DriveError::InvalidSchedule(format!("'{}': {}", expression, reason))
+            })
+        };
+        let mut days_of_week = field(weekday, 0, 7)?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week |= 1;
+        }
+        Ok(Self {
+            expression: expression.trim().to_string(),
+            minutes: field(minute, 0, 59)?,
+            hours: field(hour, 0, 23)?,
+            days_of_month: field(day, 1, 31)?,
+            months: field(month, 1, 12)?,
+            days_of_week,
+            either_day: day != "*" && weekday != "*",
+        })
+    }
+
+    /// The first time strictly after `after` that matches the schedule, at a
+    /// whole minute.
+    pub fn next_after(&self, after: SystemTime) -> Option<SystemTime> {
+        let after = OffsetDateTime::from(after);
+        let mut date = after.date();
+        // Start at the minute following `after`
+        let mut from_minute = after.hour() as u32 * 60 + after.minute() as u32 + 1;
+
+        for _ in 0..SEARCH_DAYS {
+            if self.matches_date(date) {
+                let found = (from_minute..24 * 60).find(|m| {
+                    self.hours & (1 << (m / 60)) != 0 && self.minutes & (1 << (m % 60)) != 0
+                });
+                if let Some(minute) = found {
+                    let time = Time::from_hms((minute / 60) as u8, (minute % 60) as u8, 0).ok()?;
+                    let at = date.with_time(time).assume_utc();
+                    let seconds = u64::try_from(at.unix_timestamp()).ok()?;
+                    return Some(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds));
+                }
+            }
+            date = date.next_day()?;
+            from_minute = 0;
+        }
+        None
+    }
+
+    fn matches_date(&self, date: Date) -> bool {
+        if self.months & (1 << u8::from(date.month())) == 0 {
+            return false;
+        }
+        let day = self.days_of_month & (1 << date.day()) != 0;
+        let weekday = self.days_of_week & (1 << date.weekday().number_days_from_sunday()) != 0;
+        if self.either_day {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = DriveError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+/// Parse one cron field into a bit set of the allowed values.
+fn parse_field(value: &str, min: u32, max: u32) -> std::result::Result<u64, String> {
+    let number = |s: &str| {
+        s.parse::<u32>()
+            .ok()
+            .filter(|n| (min..=max).contains(n))
+            .ok_or_else(|| format!("'{}' is not a number between {} and {}", s, min, max))
+    };
+
+    let mut bits = 0u64;
+    for part in value.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|&s| s > 0)
+                    .ok_or_else(|| format!("invalid step '{}'", step))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (number(start)?, number(end)?),
+                // `5/10` means every 10 starting at 5
+                None if step > 1 => (number(range)?, max),
+                None => {
+                    let n = number(range)?;
+                    (n, n)
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("range '{}' is backwards", range));
+        }
+        for n in (start..=end).step_by(step as usize) {
+            bits |= 1 << n;
+        }
+    }
+    Ok(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: i64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(timestamp as u64)
+    }
+
+    #[test]
+    fn test_parse_schedule() {
+        assert!(Schedule::parse("*/15 * * * *").is_ok());
+        assert!(Schedule::parse("0 2 * * 1-5").is_ok());
+        assert!(Schedule::parse("@daily").is_ok());
+        assert!(Schedule::parse("0 2 * *").is_err());
+        assert!(Schedule::parse("60 * * * *").is_err());
+        assert!(Schedule::parse("*/0 * * * *").is_err());
+        assert!(Schedule::parse("5-1 * * * *").is_err());
+    }
+
+    #[test]
+    fn test_next_after() {
+        // 2024-01-01T10:07:30Z, a Monday
+        let now = at(1_704_103_650);
+
+        let every_15 = Schedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(every_15.next_after(now), Some(at(1_704_104_100)));
+
+        // 02:00 on the next weekday
+        let nightly = Schedule::parse("0 2 * * 1-5").unwrap();
+        assert_eq!(nightly.next_after(now), Some(at(1_704_160_800)));
+
+        // Next Sunday midnight, given as 7
+        let weekly = Schedule::parse("0 0 * * 7").unwrap();
+        assert_eq!(weekly.next_after(now), Some(at(1_704_585_600)));
+
+        // Strictly after: a matching minute is not returned again
+        let hourly = Schedule::parse("@hourly").unwrap();
+        assert_eq!(hourly.next_after(at(1_704_103_200)), Some(at(1_704_106_800)));
+
+        let never = Schedule::parse("0 0 30 2 *").unwrap();
+        assert_eq!(never.next_after(now), None);
+    }
+}
@@ -0,0 +1,214 @@
+//! Persistent state for resumable uploads.
+//!
+//! Google keeps a resumable upload URL valid for about a week. Saving it
+//! together with a fingerprint of the local file lets a later process pick
+//! up a crashed upload where it left off instead of starting over.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DriveError, Result};
+
+/// How long Google keeps a resumable upload session alive (7 days).
+const SESSION_LIFETIME_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Saved state of an in-progress resumable upload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadSession {
+    /// Session URI returned by the upload initiation request.
+    pub upload_url: String,
+    /// Local file being uploaded.
+    pub local_path: PathBuf,
+    /// Destination folder ID.
+    pub parent_id: String,
+    /// Remote file name.
+    pub file_name: String,
+    /// Size of the local file when the session started.
+    pub file_size: u64,
+    /// Modification time of the local file (seconds since the Unix epoch).
+    pub modified: u64,
+    /// Number of bytes the server has acknowledged.
+    pub bytes_sent: u64,
+    /// When the session was created (seconds since the Unix epoch).
+    pub created_at: u64,
+}
+
+impl UploadSession {
+    /// Whether the session was started for a file with this size and modification time.
+    pub fn matches(&self, fingerprint: FileFingerprint) -> bool {
+        self.file_size == fingerprint.size && self.modified == fingerprint.modified
+    }
+
+    /// Whether Google has likely discarded the session by `now` (Unix seconds).
+    pub fn is_expired(&self, now: u64) -> bool {
+        now.saturating_sub(self.created_at) >= SESSION_LIFETIME_SECS
+    }
+}
+
+/// Size and modification time of a local file, used to detect changes between runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileFingerprint {
+    pub size: u64,
+    pub modified: u64,
+}
+
+impl FileFingerprint {
+    /// Read the fingerprint of a local file.
+    pub fn of<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let metadata = fs::metadata(path).map_err(|e| DriveError::FileReadError {
+            path: path.display().to_string(),
+            source: e,
+        })?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Ok(Self {
+            size: metadata.len(),
+            modified,
+        })
+    }
+}
+
+/// Directory of saved [`UploadSession`]s, one JSON file per local file and destination.
+#[derive(Debug, Clone)]
+pub struct SessionStore {
+    dir: PathBuf,
+}
+
+impl SessionStore {
+    /// Create a store rooted at `dir`. The directory is created on first save.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Default state directory: `$XDG_STATE_HOME/share_drive/uploads`,
+    /// falling back to `~/.local/state/share_drive/uploads`.
+    pub fn default_dir() -> Option<PathBuf> {
+        Some(state_home()?.join("uploads"))
+    }
+
+    /// Directory the store writes to.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Load the session for a local file and destination folder, if one was saved.
+    ///
+    /// Unreadable or corrupt session files are treated as absent.
+    pub fn load(&self, local_path: &Path, parent_id: &str) -> Option<UploadSession> {
+        let content = fs::read_to_string(self.session_path(local_path, parent_id)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Save (or replace) a session.
+    pub fn save(&self, session: &UploadSession) -> Result<()> {
+        let path = self.session_path(&session.local_path, &session.parent_id);
+        let write_err = |e| DriveError::FileWriteError {
+            path: path.display().to_string(),
+            source: e,
+        };
+
+        fs::create_dir_all(&self.dir).map_err(write_err)?;
+        let content = serde_json::to_string_pretty(session)?;
+        fs::write(&path, content).map_err(write_err)
+    }
+
+    /// Remove the saved session for a local file and destination folder, if any.
+    pub fn remove(&self, local_path: &Path, parent_id: &str) -> Result<()> {
+        let path = self.session_path(local_path, parent_id);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(DriveError::FileWriteError {
+                path: path.display().to_string(),
+                source: e,
+            }),
+        }
+    }
+
+    fn session_path(&self, local_path: &Path, parent_id: &str) -> PathBuf {
+        let local_path = fs::canonicalize(local_path).unwrap_or_else(|_| local_path.to_path_buf());
+        let key = md5::compute(format!("{}\n{}", local_path.display(), parent_id));
+        self.dir.join(format!("{:x}.json", key))
+    }
+}
+
+/// Base directory for persisted state: `$XDG_STATE_HOME/share_drive`,
+/// falling back to `~/.local/state/share_drive`.
+pub(crate) fn state_home() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_STATE_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/state")))?;
+    Some(base.join("share_drive"))
+}
+
+/// Current time in seconds since the Unix epoch.
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_session(local_path: PathBuf) -> UploadSession {
+        UploadSession {
+            upload_url: "https://www.googleapis.com/upload/drive/v3/files?upload_id=xyz".to_string(),
+            local_path,
+            parent_id: "folder123".to_string(),
+            file_name: "big.iso".to_string(),
+            file_size: 1024,
+            modified: 1_700_000_000,
+            bytes_sent: 512,
+            created_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_save_load_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SessionStore::new(dir.path().join("uploads"));
+        let local = dir.path().join("big.iso");
+        fs::write(&local, b"data").unwrap();
+
+        assert!(store.load(&local, "folder123").is_none());
+
+        let session = sample_session(local.clone());
+        store.save(&session).unwrap();
+        assert_eq!(store.load(&local, "folder123"), Some(session));
+        assert!(store.load(&local, "other-folder").is_none());
+
+        store.remove(&local, "folder123").unwrap();
+        assert!(store.load(&local, "folder123").is_none());
+        store.remove(&local, "folder123").unwrap();
+    }
+
+    #[test]
+    fn test_session_matches_and_expiry() {
+        let session = sample_session(PathBuf::from("big.iso"));
+
+        assert!(session.matches(FileFingerprint {
+            size: 1024,
+            modified: 1_700_000_000
+        }));
+        assert!(!session.matches(FileFingerprint {
+            size: 2048,
+            modified: 1_700_000_000
+        }));
+
+        assert!(!session.is_expired(1_700_000_000 + 60));
+        assert!(session.is_expired(1_700_000_000 + SESSION_LIFETIME_SECS));
+    }
+}
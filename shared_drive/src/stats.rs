@@ -0,0 +1,228 @@
+//! Counters of what a [`SharedDriveClient`](crate::SharedDriveClient) has
+//! done: bytes moved, API calls by endpoint and retries.
+//!
+//! Every client keeps a [`TransferStats`], read with
+//! [`SharedDriveClient::stats`](crate::SharedDriveClient::stats). Byte
+//! counts cover file content only, not metadata or protocol overhead.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::models::format_size;
+
+/// Calls made to one API endpoint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct EndpointStats {
+    /// Requests sent, including retries.
+    pub calls: u64,
+    /// Time spent waiting for responses, summed over all calls.
+    #[serde(serialize_with = "serialize_millis", rename = "duration_ms")]
+    pub duration: Duration,
+}
+
+/// Thread-safe transfer counters shared by all operations of a client.
+#[derive(Debug)]
+pub struct TransferStats {
+    started: Mutex<Instant>,
+    uploaded: AtomicU64,
+    downloaded: AtomicU64,
+    retries: AtomicU64,
+    /// By endpoint, e.g. `GET /files/{id}`.
+    endpoints: Mutex<BTreeMap<String, EndpointStats>>,
+}
+
+impl Default for TransferStats {
+    fn default() -> Self {
+        Self {
+            started: Mutex::new(Instant::now()),
+            uploaded: AtomicU64::new(0),
+            downloaded: AtomicU64::new(0),
+            retries: AtomicU64::new(0),
+            endpoints: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl TransferStats {
+    /// Bytes of file content sent to Drive.
+    pub fn uploaded_bytes(&self) -> u64 {
+        self.uploaded.load(Ordering::Relaxed)
+    }
+
+    /// Bytes of file content received from Drive.
+    pub fn downloaded_bytes(&self) -> u64 {
+        self.downloaded.load(Ordering::Relaxed)
+    }
+
+    /// Requests repeated after a transient failure or throttling.
+    pub fn retries(&self) -> u64 {
+        self.retries.load(Ordering::Relaxed)
+    }
+
+    /// API calls so far, by endpoint.
+    pub fn endpoints(&self) -> BTreeMap<String, EndpointStats> {
+        self.endpoints.lock().unwrap().clone()
+    }
+
+    /// Total number of API calls.
+    pub fn api_calls(&self) -> u64 {
+        self.endpoints.lock().unwrap().values().map(|e| e.calls).sum()
+    }
+
+    /// Time since the client was created or the counters were reset.
+    pub fn elapsed(&self) -> Duration {
+        self.started.lock().unwrap().elapsed()
+    }
+
+    /// Set all counters to zero and restart [`Self::elapsed`].
+    pub fn reset(&self) {
+        *self.started.lock().unwrap() = Instant::now();
+        self.uploaded.store(0, Ordering::Relaxed);
+        self.downloaded.store(0, Ordering::Relaxed);
+        self.retries.store(0, Ordering::Relaxed);
+        self.endpoints.lock().unwrap().clear();
+    }
+
+    pub(crate) fn add_uploaded(&self, bytes: u64) {
+        self.uploaded.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_downloaded(&self, bytes: u64) {
+        self.downloaded.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_retries(&self, retries: u64) {
+        self.retries.fetch_add(retries, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_call(&self, endpoint: String, duration: Duration) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let entry = endpoints.entry(endpoint).or_default();
+        entry.calls += 1;
+        entry.duration += duration;
+    }
+}
+
+impl fmt::Display for TransferStats {
+    /// One line such as `12 API calls (1 retried), 3.20 MB up, 0 B down in 4.1s`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} API calls", self.api_calls())?;
+        if self.retries() > 0 {
+            write!(f, " ({} retried)", self.retries())?;
+        }
+        write!(
+            f,
+            ", {} up, {} down in {:.1}s",
+            format_size(self.uploaded_bytes()),
+            format_size(self.downloaded_bytes()),
+            self.elapsed().as_secs_f64()
+        )
+    }
+}
+
+impl Serialize for TransferStats {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("TransferStats", 5)?;
+        state.serialize_field("uploaded_bytes", &self.uploaded_bytes())?;
+        state.serialize_field("downloaded_bytes", &self.downloaded_bytes())?;
+        state.serialize_field("retries", &self.retries())?;
+        state.serialize_field("elapsed_ms", &(self.elapsed().as_millis() as u64))?;
+        state.serialize_field("endpoints", &self.endpoints())?;
+        state.end()
+    }
+}
+
+fn serialize_millis<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis() as u64)
+}
+
+/// Name of the endpoint a request goes to: the method and the path below the
+/// API version, with IDs replaced by `{id}`, e.g. `PATCH /files/{id}`.
+/// Uploads are prefixed with `upload`.
+pub(crate) fn endpoint_name(method: &str, path: &str) -> String {
+    const RESOURCES: &[&str] = &[
+        "about",
+        "changes",
+        "channels",
+        "comments",
+        "copy",
+        "drives",
+        "emptyTrash",
+        "export",
+        "files",
+        "generateIds",
+        "hide",
+        "permissions",
+        "replies",
+        "revisions",
+        "startPageToken",
+        "stop",
+        "unhide",
+        "watch",
+    ];
+    if path.starts_with("/batch/") {
+        return format!("{} batch", method);
+    }
+    let (upload, rest) = match path.split_once("/v3/") {
+        Some((prefix, rest)) => (prefix.ends_with("/upload/drive"), rest),
+        None => (false, path.trim_start_matches('/')),
+    };
+    let mut name = String::from(method);
+    if upload {
+        name.push_str(" upload");
+    }
+    name.push(' ');
+    for segment in rest.split('/').filter(|s| !s.is_empty()) {
+        name.push('/');
+        name.push_str(if RESOURCES.contains(&segment) { segment } else { "{id}" });
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_name() {
+        assert_eq!(endpoint_name("GET", "/drive/v3/files"), "GET /files");
+        assert_eq!(endpoint_name("GET", "/drive/v3/files/1AbC"), "GET /files/{id}");
+        assert_eq!(
+            endpoint_name("DELETE", "/drive/v3/files/1AbC/permissions/0123"),
+            "DELETE /files/{id}/permissions/{id}"
+        );
+        assert_eq!(endpoint_name("POST", "/upload/drive/v3/files"), "POST upload /files");
+        assert_eq!(endpoint_name("POST", "/batch/drive/v3"), "POST batch");
+        assert_eq!(
+            endpoint_name("GET", "/drive/v3/changes/startPageToken"),
+            "GET /changes/startPageToken"
+        );
+    }
+
+    #[test]
+    fn test_transfer_stats() {
+        let stats = TransferStats::default();
+        stats.record_call(endpoint_name("GET", "/drive/v3/files/a"), Duration::from_millis(30));
+        stats.record_call(endpoint_name("GET", "/drive/v3/files/b"), Duration::from_millis(20));
+        stats.add_retries(1);
+        stats.add_uploaded(2048);
+
+        let endpoints = stats.endpoints();
+        assert_eq!(endpoints["GET /files/{id}"].calls, 2);
+        assert_eq!(endpoints["GET /files/{id}"].duration, Duration::from_millis(50));
+        assert!(stats.to_string().starts_with("2 API calls (1 retried), 2.00 KB up, 0 B down"));
+
+        stats.reset();
+        assert_eq!(stats.api_calls(), 0);
+        assert_eq!(stats.uploaded_bytes(), 0);
+    }
+}
@@ -0,0 +1,250 @@
+//! `--format` templates that render [`FileMetadata`] with placeholders.
+
+use std::str::FromStr;
+
+use crate::error::{DriveError, Result};
+use crate::models::{format_size, FileMetadata};
+
+/// A field of [`FileMetadata`] that can appear in a template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Id,
+    Name,
+    Size,
+    HumanSize,
+    MimeType,
+    WebViewLink,
+    Md5Checksum,
+    Sha256Checksum,
+    CreatedTime,
+    ModifiedTime,
+    Parents,
+    Owners,
+    Trashed,
+    ShortcutTargetId,
+    Description,
+}
+
+impl Field {
+    /// Placeholder names, matching the keys of the JSON output.
+    const NAMES: &'static [(&'static str, Field)] = &[
+        ("id", Field::Id),
+        ("name", Field::Name),
+        ("size", Field::Size),
+        ("humanSize", Field::HumanSize),
+        ("mimeType", Field::MimeType),
+        ("webViewLink", Field::WebViewLink),
+        ("md5Checksum", Field::Md5Checksum),
+        ("sha256Checksum", Field::Sha256Checksum),
+        ("createdTime", Field::CreatedTime),
+        ("modifiedTime", Field::ModifiedTime),
+        ("parents", Field::Parents),
+        ("owners", Field::Owners),
+        ("trashed", Field::Trashed),
+        ("shortcutTargetId", Field::ShortcutTargetId),
+        ("description", Field::Description),
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, field)| *field)
+    }
+
+    /// Value of the field for `file`; absent values render as an empty string
+    /// and lists are joined with commas.
+    fn value(self, file: &FileMetadata) -> String {
+        match self {
+            Field::Id => file.id.clone(),
+            Field::Name => file.name.clone(),
+            Field::Size => file.size.map(|s| s.to_string()).unwrap_or_default(),
+            Field::HumanSize => file.size.map(format_size).unwrap_or_default(),
+            Field::MimeType => file.mime_type.clone().unwrap_or_default(),
+            Field::WebViewLink => file.web_view_link.clone().unwrap_or_default(),
+            Field::Md5Checksum => file.md5_checksum.clone().unwrap_or_default(),
+            Field::Sha256Checksum => file.sha256_checksum.clone().unwrap_or_default(),
+            Field::CreatedTime => file.created_time.clone().unwrap_or_default(),
+            Field::ModifiedTime => file.modified_time.clone().unwrap_or_default(),
+            Field::Parents => file.parents.join(","),
+            Field::Owners => file
+                .owners
+                .iter()
+                .filter_map(|o| o.email_address.as_deref().or(o.display_name.as_deref()))
+                .collect::<Vec<_>>()
+                .join(","),
+            Field::Trashed => file.trashed.to_string(),
+            Field::ShortcutTargetId => file
+                .shortcut_details
+                .as_ref()
+                .map(|d| d.target_id.clone())
+                .unwrap_or_default(),
+            Field::Description => file.description.clone().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Field(Field),
+}
+
+/// A parsed output template such as `{id}\t{name}\t{size}`.
+///
+/// Placeholders use the JSON field names (`id`, `name`, `size`, `mimeType`,
+/// `modifiedTime`, `parents`, ...) plus `humanSize` and `shortcutTargetId`;
+/// see [`FormatTemplate::placeholders`]. `{{` and `}}` produce
+/// literal braces, and `\t`, `\n` and `\\` are unescaped so templates can be
+/// passed in single quotes on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatTemplate {
+    segments: Vec<Segment>,
+}
+
+impl FormatTemplate {
+    /// Parse a template, rejecting unknown placeholders and unbalanced braces.
+    pub fn parse(template: &str) -> Result<Self> {
+        let invalid = |reason: String| DriveError::InvalidTemplate(reason);
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(invalid(format!("unclosed '{{{}'", name))),
+                        }
+                    }
+                    let field = Field::from_name(name.trim()).ok_or_else(|| {
+                        let known: Vec<&str> = Self::placeholders().collect();
+                        invalid(format!(
+                            "unknown placeholder '{{{}}}', expected one of: {}",
+                            name,
+                            known.join(", ")
+                        ))
+                    })?;
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Field(field));
+                }
+                '}' => return Err(invalid("unmatched '}' (use '}}' for a literal brace)".into())),
+                '\\' => match chars.peek() {
+                    Some('t') => {
+                        chars.next();
+                        literal.push('\t');
+                    }
+                    Some('n') => {
+                        chars.next();
+                        literal.push('\n');
+                    }
+                    Some('\\') => {
+                        chars.next();
+                        literal.push('\\');
+                    }
+                    _ => literal.push('\\'),
+                },
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Names of all supported placeholders.
+    pub fn placeholders() -> impl Iterator<Item = &'static str> {
+        Field::NAMES.iter().map(|(name, _)| *name)
+    }
+
+    /// Render the template for one file.
+    pub fn render(&self, file: &FileMetadata) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(text) => text.clone(),
+                Segment::Field(field) => field.value(file),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for FormatTemplate {
+    type Err = DriveError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_file() -> FileMetadata {
+        FileMetadata {
+            id: "abc123".to_string(),
+            name: "report.pdf".to_string(),
+            mime_type: Some("application/pdf".to_string()),
+            web_view_link: None,
+            size: Some(2048),
+            md5_checksum: None,
+            parents: vec!["p1".to_string(), "p2".to_string()],
+            trashed: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_placeholders() {
+        let template = FormatTemplate::parse(r"{id}\t{name}\t{size}").unwrap();
+        assert_eq!(template.render(&sample_file()), "abc123\treport.pdf\t2048");
+
+        let template = FormatTemplate::parse("{name} ({humanSize}, {mimeType})").unwrap();
+        assert_eq!(
+            template.render(&sample_file()),
+            "report.pdf (2.00 KB, application/pdf)"
+        );
+    }
+
+    #[test]
+    fn test_missing_values_render_empty() {
+        let template = FormatTemplate::parse("[{webViewLink}][{md5Checksum}]").unwrap();
+        assert_eq!(template.render(&sample_file()), "[][]");
+    }
+
+    #[test]
+    fn test_render_extended_fields() {
+        let template = FormatTemplate::parse("{parents} {trashed} [{owners}]").unwrap();
+        assert_eq!(template.render(&sample_file()), "p1,p2 true []");
+    }
+
+    #[test]
+    fn test_escapes_and_literal_braces() {
+        let template = FormatTemplate::parse(r"{{{id}}}\n\\").unwrap();
+        assert_eq!(template.render(&sample_file()), "{abc123}\n\\");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(FormatTemplate::parse("{owner}").is_err());
+        assert!(FormatTemplate::parse("{id").is_err());
+        assert!(FormatTemplate::parse("id}").is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! Bandwidth and request-rate limiting.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::error::{DriveError, Result};
+use crate::models::parse_size;
+
+/// Longest gap [`RequestRateLimiter`] leaves between two requests.
+const MAX_REQUEST_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Schedules work back to back: each caller reserves a slot of a given
+/// duration after the previous reservation and waits for its slot to start.
+#[derive(Debug)]
+struct Pacer {
+    next_free: Mutex<Instant>,
+}
+
+impl Pacer {
+    fn new() -> Self {
+        Self {
+            next_free: Mutex::new(Instant::now()),
+        }
+    }
+
+    async fn wait(&self, cost: Duration) {
+        let start = {
+            let mut next_free = self.next_free.lock().unwrap_or_else(|e| e.into_inner());
+            let start = (*next_free).max(Instant::now());
+            *next_free = start + cost;
+            start
+        };
+        tokio::time::sleep_until(start).await;
+    }
+}
+
+/// Caps the combined throughput of all transfers that share it.
+///
+/// Callers report each chunk before (uploads) or after (downloads) moving it
+/// and are delayed so the average rate stays at or below the limit.
+#[derive(Debug)]
+pub struct BandwidthLimiter {
+    bytes_per_second: u64,
+    pacer: Pacer,
+}
+
+impl BandwidthLimiter {
+    /// Create a limiter allowing `bytes_per_second` (at least 1).
+    pub fn new(bytes_per_second: u64) -> Self {
+        Self {
+            bytes_per_second: bytes_per_second.max(1),
+            pacer: Pacer::new(),
+        }
+    }
+
+    /// The configured limit in bytes per second.
+    pub fn bytes_per_second(&self) -> u64 {
+        self.bytes_per_second
+    }
+
+    /// Account for `bytes` of traffic, waiting until the budget allows it.
+    pub async fn acquire(&self, bytes: usize) {
+        let cost = Duration::from_secs_f64(bytes as f64 / self.bytes_per_second as f64);
+        self.pacer.wait(cost).await;
+    }
+}
+
+/// Spaces out API requests so they stay under a requests-per-second quota.
+#[derive(Debug)]
+pub struct RequestRateLimiter {
+    requests_per_second: f64,
+    pacer: Pacer,
+}
+
+impl RequestRateLimiter {
+    /// Create a limiter allowing `requests_per_second`, which must be a
+    /// positive number. Rates below one request per hour are treated as one
+    /// per hour.
+    pub fn new(requests_per_second: f64) -> Result<Self> {
+        if !(requests_per_second.is_finite() && requests_per_second > 0.0) {
+            return Err(DriveError::InvalidRateLimit(requests_per_second.to_string()));
+        }
+        Ok(Self {
+            requests_per_second,
+            pacer: Pacer::new(),
+        })
+    }
+
+    /// The configured limit in requests per second.
+    pub fn requests_per_second(&self) -> f64 {
+        self.requests_per_second
+    }
+
+    /// Wait until another request may be sent.
+    pub async fn acquire(&self) {
+        let interval = Duration::try_from_secs_f64(1.0 / self.requests_per_second)
+            .map_or(MAX_REQUEST_INTERVAL, |interval| interval.min(MAX_REQUEST_INTERVAL));
+        self.pacer.wait(interval).await;
+    }
+}
+
+/// Parse a bandwidth limit such as `500K`, `5M`, `1.5G` or `1048576` into bytes per second.
+///
+/// Suffixes are binary (K = 1024) and case-insensitive; a trailing `B` or
+/// `/s` is accepted (`5MB/s`). Returns `None` for malformed or zero values.
+pub fn parse_bandwidth(input: &str) -> Option<u64> {
+    let s = input.trim();
+    let bytes = parse_size(s.strip_suffix("/s").unwrap_or(s))?;
+    (bytes > 0).then_some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bandwidth() {
+        assert_eq!(parse_bandwidth("1024"), Some(1024));
+        assert_eq!(parse_bandwidth("500K"), Some(500 * 1024));
+        assert_eq!(parse_bandwidth("5M"), Some(5 * 1024 * 1024));
+        assert_eq!(parse_bandwidth("5m"), Some(5 * 1024 * 1024));
+        assert_eq!(parse_bandwidth("1.5G"), Some(1536 * 1024 * 1024));
+        assert_eq!(parse_bandwidth("5MB/s"), Some(5 * 1024 * 1024));
+    }
+
+    #[test]
+    fn test_parse_bandwidth_invalid() {
+        assert_eq!(parse_bandwidth(""), None);
+        assert_eq!(parse_bandwidth("0"), None);
+        assert_eq!(parse_bandwidth("-5M"), None);
+        assert_eq!(parse_bandwidth("fast"), None);
+        assert_eq!(parse_bandwidth("M"), None);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_paces_transfers() {
+        let limiter = BandwidthLimiter::new(1000);
+        let start = Instant::now();
+
+        limiter.acquire(50).await;
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        limiter.acquire(50).await;
+        limiter.acquire(50).await;
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn test_request_rate_limiter_spaces_requests() {
+        let limiter = RequestRateLimiter::new(20.0).unwrap();
+        let start = Instant::now();
+
+        limiter.acquire().await;
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_request_rate_limiter_rejects_invalid_rates() {
+        for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(RequestRateLimiter::new(rate), Err(DriveError::InvalidRateLimit(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tiny_rate_does_not_overflow() {
+        let limiter = RequestRateLimiter::new(1e-20).unwrap();
+        limiter.acquire().await;
+        let second = tokio::time::timeout(Duration::from_millis(10), limiter.acquire()).await;
+        assert!(second.is_err());
+    }
+}
@@ -21,10 +21,61 @@ static OPEN_URL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
         .expect("Invalid open URL regex")
 });
 
+/// Direct download links, with the ID anywhere in the query string.
+static DOWNLOAD_URL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"^https?://(?:drive\.google\.com/uc|drive\.usercontent\.google\.com/download)",
+        r"\?(?:[^#]*&)?id=([a-zA-Z0-9_-]+)"
+    ))
+    .expect("Invalid download URL regex")
+});
+
+/// Links to Google Docs, Sheets, Slides, Forms and Drawings.
+static DOCS_URL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"^https?://docs\.google\.com/",
+        r"(document|spreadsheets|presentation|forms|drawings)/(?:u/\d+/)?d/([a-zA-Z0-9_-]+)"
+    ))
+    .expect("Invalid docs URL regex")
+});
+
+/// `resourcekey` query parameter of links shared before Drive's 2021 security update.
+static RESOURCE_KEY_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"[?&]resourcekey=([a-zA-Z0-9_-]+)").expect("Invalid resource key regex")
+});
+
 /// Valid Google Drive ID pattern (alphanumeric, underscore, hyphen).
 static ID_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"^[a-zA-Z0-9_-]+$").expect("Invalid ID regex"));
 
+/// Prefix of remote paths such as `drive:/Reports/2024/summary.xlsx`.
+pub const DRIVE_PATH_PREFIX: &str = "drive:";
+
+/// Split a remote path such as `drive:/Reports/2024/summary.xlsx` into the
+/// names along it, from the drive root down.
+///
+/// Returns `None` when `input` is not a remote path. `drive:/` denotes the
+/// root of the drive and gives an empty list; empty components are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use share_drive::url_parser::parse_drive_path;
+///
+/// let path = parse_drive_path("drive:/Reports/2024/summary.xlsx").unwrap();
+/// assert_eq!(path, ["Reports", "2024", "summary.xlsx"]);
+/// assert!(parse_drive_path("1abc123").is_none());
+/// ```
+pub fn parse_drive_path(input: &str) -> Option<Vec<String>> {
+    let path = input.trim().strip_prefix(DRIVE_PATH_PREFIX)?;
+    Some(
+        path.split('/')
+            .filter(|component| !component.is_empty())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
 /// Extract a Google Drive ID from a URL or validate a raw ID.
 ///
 /// Supports the following URL formats:
@@ -32,6 +83,10 @@ static ID_REGEX: LazyLock<Regex> =
 /// - `https://drive.google.com/drive/u/0/folders/<ID>`
 /// - `https://drive.google.com/file/d/<ID>/view`
 /// - `https://drive.google.com/open?id=<ID>`
+/// - `https://drive.google.com/uc?id=<ID>&export=download`
+/// - `https://drive.usercontent.google.com/download?id=<ID>`
+/// - `https://docs.google.com/document/d/<ID>/edit`, and likewise for
+///   `spreadsheets`, `presentation`, `forms` and `drawings`
 /// - Raw ID string
 ///
 /// # Examples
@@ -46,32 +101,173 @@ static ID_REGEX: LazyLock<Regex> =
 /// assert_eq!(id, "1abc123");
 /// ```
 pub fn extract_id(url_or_id: &str) -> Result<String> {
+    extract_id_with_resource_key(url_or_id).map(|(id, _)| id)
+}
+
+/// Like [`extract_id`], also returning the `resourcekey` of older shared
+/// links. Drive needs it, sent with
+/// [`SharedDriveClient::add_resource_key`](crate::SharedDriveClient::add_resource_key),
+/// to serve those files.
+///
+/// # Examples
+///
+/// ```
+/// use share_drive::url_parser::extract_id_with_resource_key;
+///
+/// let url = "https://drive.google.com/file/d/1abc123/view?resourcekey=0-xyz";
+/// let (id, key) = extract_id_with_resource_key(url).unwrap();
+/// assert_eq!(id, "1abc123");
+/// assert_eq!(key.as_deref(), Some("0-xyz"));
+/// ```
+pub fn extract_id_with_resource_key(url_or_id: &str) -> Result<(String, Option<String>)> {
+    let (target, resource_key) = extract_ref(url_or_id)?;
+    Ok((target.id().to_string(), resource_key))
+}
+
+/// What a Drive link or ID refers to, as far as the link itself tells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DriveRef {
+    /// A folder link (`/drive/folders/<ID>`).
+    Folder(String),
+    /// A file link (`/file/d/<ID>`) or direct download link.
+    File(String),
+    /// A Google Docs, Sheets, Slides, Forms or Drawings link.
+    Doc { id: String, kind: DocKind },
+    /// A raw ID or `open?id=` link, which may be a file or a folder.
+    Unknown(String),
+}
+
+/// Google editor a [`DriveRef::Doc`] link opens in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocKind {
+    Document,
+    Spreadsheet,
+    Presentation,
+    Form,
+    Drawing,
+}
+
+impl DocKind {
+    /// Google-native MIME type of files of this kind.
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            DocKind::Document => "application/vnd.google-apps.document",
+            DocKind::Spreadsheet => "application/vnd.google-apps.spreadsheet",
+            DocKind::Presentation => "application/vnd.google-apps.presentation",
+            DocKind::Form => "application/vnd.google-apps.form",
+            DocKind::Drawing => "application/vnd.google-apps.drawing",
+        }
+    }
+
+    /// Kind for the path segment after `docs.google.com/`.
+    fn from_segment(segment: &str) -> Option<Self> {
+        match segment {
+            "document" => Some(DocKind::Document),
+            "spreadsheets" => Some(DocKind::Spreadsheet),
+            "presentation" => Some(DocKind::Presentation),
+            "forms" => Some(DocKind::Form),
+            "drawings" => Some(DocKind::Drawing),
+            _ => None,
+        }
+    }
+}
+
+impl DriveRef {
+    /// The Drive ID, whatever it refers to.
+    pub fn id(&self) -> &str {
+        match self {
+            DriveRef::Folder(id) | DriveRef::File(id) | DriveRef::Unknown(id) => id,
+            DriveRef::Doc { id, .. } => id,
+        }
+    }
+
+    /// The ID, unless the link is known to point at something other than a
+    /// folder. Raw IDs are accepted; Drive rejects them later if needed.
+    pub fn into_folder_id(self) -> Result<String> {
+        match self {
+            DriveRef::Folder(id) | DriveRef::Unknown(id) => Ok(id),
+            DriveRef::File(id) | DriveRef::Doc { id, .. } => Err(DriveError::NotAFolder(id)),
+        }
+    }
+}
+
+/// Like [`extract_id_with_resource_key`], also telling whether the link
+/// points at a folder, a file or a Google editor document, so that callers
+/// can reject, say, a file link given to a folder command.
+///
+/// # Examples
+///
+/// ```
+/// use share_drive::url_parser::{extract_ref, DocKind, DriveRef};
+///
+/// let (target, _) = extract_ref("https://drive.google.com/drive/folders/1abc123").unwrap();
+/// assert_eq!(target, DriveRef::Folder("1abc123".to_string()));
+///
+/// let (target, _) = extract_ref("https://docs.google.com/spreadsheets/d/1abc123/edit").unwrap();
+/// assert_eq!(
+///     target,
+///     DriveRef::Doc { id: "1abc123".to_string(), kind: DocKind::Spreadsheet }
+/// );
+/// assert!(target.into_folder_id().is_err());
+/// ```
+pub fn extract_ref(url_or_id: &str) -> Result<(DriveRef, Option<String>)> {
     let trimmed = url_or_id.trim();
+    let target = ref_from(url_or_id, trimmed)?;
+    let resource_key = RESOURCE_KEY_REGEX
+        .captures(trimmed)
+        .and_then(|captures| captures.get(1))
+        .map(|key| key.as_str().to_string());
+    Ok((target, resource_key))
+}
 
+/// What the URL or raw ID `trimmed` refers to, with `url_or_id` as given for errors.
+fn ref_from(url_or_id: &str, trimmed: &str) -> Result<DriveRef> {
     // Try folder URL pattern
     if let Some(captures) = FOLDER_URL_REGEX.captures(trimmed) {
         if let Some(id) = captures.get(1) {
-            return Ok(id.as_str().to_string());
+            return Ok(DriveRef::Folder(id.as_str().to_string()));
         }
     }
 
     // Try file URL pattern
     if let Some(captures) = FILE_URL_REGEX.captures(trimmed) {
         if let Some(id) = captures.get(1) {
-            return Ok(id.as_str().to_string());
+            return Ok(DriveRef::File(id.as_str().to_string()));
         }
     }
 
     // Try open URL pattern
     if let Some(captures) = OPEN_URL_REGEX.captures(trimmed) {
         if let Some(id) = captures.get(1) {
-            return Ok(id.as_str().to_string());
+            return Ok(DriveRef::Unknown(id.as_str().to_string()));
+        }
+    }
+
+    // Try download URL pattern
+    if let Some(captures) = DOWNLOAD_URL_REGEX.captures(trimmed) {
+        if let Some(id) = captures.get(1) {
+            return Ok(DriveRef::File(id.as_str().to_string()));
+        }
+    }
+
+    // Try Google editor URL pattern
+    if let Some(captures) = DOCS_URL_REGEX.captures(trimmed) {
+        if let (Some(kind), Some(id)) = (
+            captures
+                .get(1)
+                .and_then(|kind| DocKind::from_segment(kind.as_str())),
+            captures.get(2),
+        ) {
+            return Ok(DriveRef::Doc {
+                id: id.as_str().to_string(),
+                kind,
+            });
         }
     }
 
     // Check if it's a raw ID
     if ID_REGEX.is_match(trimmed) && !trimmed.is_empty() {
-        return Ok(trimmed.to_string());
+        return Ok(DriveRef::Unknown(trimmed.to_string()));
     }
 
     Err(DriveError::InvalidUrlOrId(url_or_id.to_string()))
@@ -111,6 +307,32 @@ mod tests {
         assert_eq!(extract_id(url).unwrap(), "1abc123XYZ");
     }
 
+    #[test]
+    fn test_extract_download_url() {
+        let url = "https://drive.google.com/uc?id=1abc123XYZ&export=download";
+        assert_eq!(extract_id(url).unwrap(), "1abc123XYZ");
+
+        let url = "https://drive.google.com/uc?export=download&id=1abc123XYZ";
+        assert_eq!(extract_id(url).unwrap(), "1abc123XYZ");
+
+        let url = "https://drive.usercontent.google.com/download?id=1abc123XYZ&export=download";
+        assert_eq!(extract_id(url).unwrap(), "1abc123XYZ");
+
+        assert!(extract_id("https://drive.google.com/uc?export=download").is_err());
+    }
+
+    #[test]
+    fn test_extract_docs_url() {
+        let url = "https://docs.google.com/document/d/1abc123XYZ/edit";
+        assert_eq!(extract_id(url).unwrap(), "1abc123XYZ");
+
+        let url = "https://docs.google.com/spreadsheets/d/1abc123XYZ/edit#gid=0";
+        assert_eq!(extract_id(url).unwrap(), "1abc123XYZ");
+
+        let url = "https://docs.google.com/presentation/u/1/d/1abc123XYZ/edit?usp=sharing";
+        assert_eq!(extract_id(url).unwrap(), "1abc123XYZ");
+    }
+
     #[test]
     fn test_extract_raw_id() {
         assert_eq!(extract_id("1abc123XYZ").unwrap(), "1abc123XYZ");
@@ -122,6 +344,86 @@ mod tests {
         assert_eq!(extract_id("  1abc123XYZ  ").unwrap(), "1abc123XYZ");
     }
 
+    #[test]
+    fn test_extract_resource_key() {
+        let url =
+            "https://drive.google.com/drive/folders/1abc123XYZ?resourcekey=0-AbC_d&usp=sharing";
+        let (id, key) = extract_id_with_resource_key(url).unwrap();
+        assert_eq!(id, "1abc123XYZ");
+        assert_eq!(key.as_deref(), Some("0-AbC_d"));
+
+        let url = "https://drive.google.com/file/d/1abc123XYZ/view?usp=sharing&resourcekey=0-xyz";
+        assert_eq!(extract_id_with_resource_key(url).unwrap().1.as_deref(), Some("0-xyz"));
+        assert_eq!(extract_id(url).unwrap(), "1abc123XYZ");
+
+        let url = "https://drive.google.com/file/d/1abc123XYZ/view";
+        assert_eq!(extract_id_with_resource_key(url).unwrap().1, None);
+        assert_eq!(extract_id_with_resource_key("1abc123XYZ").unwrap().1, None);
+    }
+
+    #[test]
+    fn test_extract_ref() {
+        let url = "https://drive.google.com/drive/u/0/folders/1abc123XYZ?resourcekey=0-xyz";
+        let (target, key) = extract_ref(url).unwrap();
+        assert_eq!(target, DriveRef::Folder("1abc123XYZ".to_string()));
+        assert_eq!(key.as_deref(), Some("0-xyz"));
+
+        let url = "https://drive.google.com/uc?id=1abc123XYZ&export=download";
+        assert_eq!(
+            extract_ref(url).unwrap().0,
+            DriveRef::File("1abc123XYZ".to_string())
+        );
+
+        let url = "https://docs.google.com/presentation/d/1abc123XYZ/edit";
+        assert_eq!(
+            extract_ref(url).unwrap().0,
+            DriveRef::Doc {
+                id: "1abc123XYZ".to_string(),
+                kind: DocKind::Presentation
+            }
+        );
+
+        let url = "https://drive.google.com/open?id=1abc123XYZ";
+        assert_eq!(
+            extract_ref(url).unwrap().0,
+            DriveRef::Unknown("1abc123XYZ".to_string())
+        );
+        assert_eq!(
+            extract_ref("1abc123XYZ").unwrap().0,
+            DriveRef::Unknown("1abc123XYZ".to_string())
+        );
+    }
+
+    #[test]
+    fn test_into_folder_id() {
+        let folder = DriveRef::Folder("1abc".to_string());
+        assert_eq!(folder.into_folder_id().unwrap(), "1abc");
+        assert_eq!(
+            DriveRef::Unknown("1abc".to_string())
+                .into_folder_id()
+                .unwrap(),
+            "1abc"
+        );
+
+        let err = DriveRef::File("1abc".to_string())
+            .into_folder_id()
+            .unwrap_err();
+        assert!(matches!(err, DriveError::NotAFolder(id) if id == "1abc"));
+        let doc = DriveRef::Doc {
+            id: "1abc".to_string(),
+            kind: DocKind::Document,
+        };
+        assert!(doc.into_folder_id().is_err());
+    }
+
+    #[test]
+    fn test_parse_drive_path() {
+        assert_eq!(parse_drive_path("drive:/a/b c/d.txt").unwrap(), ["a", "b c", "d.txt"]);
+        assert_eq!(parse_drive_path("drive:Reports//2024/").unwrap(), ["Reports", "2024"]);
+        assert!(parse_drive_path("drive:/").unwrap().is_empty());
+        assert!(parse_drive_path("https://drive.google.com/drive/folders/1abc").is_none());
+    }
+
     #[test]
     fn test_invalid_url() {
         assert!(extract_id("https://example.com/folder/123").is_err());
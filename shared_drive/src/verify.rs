@@ -0,0 +1,164 @@
+//! Read-only comparison of a local directory tree against a remote folder.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::checksum::md5_file;
+use crate::client::SharedDriveClient;
+use crate::error::{DriveError, Result};
+
+/// Differences between a local directory and a remote folder.
+///
+/// All paths are relative to the compared roots and use `/` as separator.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct VerifyReport {
+    /// Files present locally but not in the remote folder.
+    pub missing: Vec<String>,
+    /// Files present in the remote folder but not locally.
+    pub extra: Vec<String>,
+    /// Files present on both sides whose checksum (or size) differs.
+    pub mismatched: Vec<String>,
+    /// Number of files that are identical on both sides.
+    pub matched: usize,
+}
+
+impl VerifyReport {
+    /// Whether the two trees are identical.
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.mismatched.is_empty()
+    }
+}
+
+/// Compare a local directory against a remote folder without transferring any file data.
+///
+/// Remote files with an `md5Checksum` are compared by checksum; Google-native
+/// files without one fall back to a size comparison.
+pub async fn verify_folder<P: AsRef<Path>>(
+    client: &SharedDriveClient,
+    local_dir: P,
+    folder_id: &str,
+) -> Result<VerifyReport> {
+    let local: BTreeMap<String, PathBuf> = local_files(local_dir.as_ref())?.into_iter().collect();
+    let remote: BTreeMap<_, _> = client
+        .list_files_recursive(folder_id)
+        .await?
+        .into_iter()
+        .collect();
+
+    let mut report = VerifyReport::default();
+
+    for (rel_path, local_path) in &local {
+        let Some(remote_file) = remote.get(rel_path) else {
+            report.missing.push(rel_path.clone());
+            continue;
+        };
+
+        let identical = match remote_file.md5_checksum.as_deref() {
+            Some(remote_md5) => md5_file(local_path).await? == remote_md5,
+            None => {
+                let local_size = fs::metadata(local_path)
+                    .map_err(|e| DriveError::FileReadError {
+                        path: local_path.display().to_string(),
+                        source: e,
+                    })?
+                    .len();
+                remote_file.size == Some(local_size)
+            }
+        };
+
+        if identical {
+            report.matched += 1;
+        } else {
+            report.mismatched.push(rel_path.clone());
+        }
+    }
+
+    report.extra = remote
+        .keys()
+        .filter(|path| !local.contains_key(*path))
+        .cloned()
+        .collect();
+
+    Ok(report)
+}
+
+/// Recursively collect regular files below `root`.
+///
+/// Returns `(relative_path, absolute_path)` pairs sorted by relative path.
+pub fn local_files(root: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut result = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir).map_err(|e| DriveError::FileReadError {
+            path: dir.display().to_string(),
+            source: e,
+        })?;
+
+        for entry in entries {
+            let entry = entry.map_err(|e| DriveError::FileReadError {
+                path: dir.display().to_string(),
+                source: e,
+            })?;
+            let path = entry.path();
+
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.is_file() {
+                let rel_path = path
+                    .strip_prefix(root)
+                    .unwrap_or(&path)
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                result.push((rel_path, path));
+            }
+        }
+    }
+
+    result.sort();
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_files_nested() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sub/deeper")).unwrap();
+        fs::write(dir.path().join("a.txt"), b"a").unwrap();
+        fs::write(dir.path().join("sub/b.txt"), b"b").unwrap();
+        fs::write(dir.path().join("sub/deeper/c.txt"), b"c").unwrap();
+
+        let files: Vec<String> = local_files(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|(rel, _)| rel)
+            .collect();
+
+        assert_eq!(files, vec!["a.txt", "sub/b.txt", "sub/deeper/c.txt"]);
+    }
+
+    #[test]
+    fn test_local_files_missing_dir() {
+        assert!(local_files(Path::new("/nonexistent/dir")).is_err());
+    }
+
+    #[test]
+    fn test_report_is_clean() {
+        let mut report = VerifyReport {
+            matched: 3,
+            ..Default::default()
+        };
+        assert!(report.is_clean());
+
+        report.extra.push("stale.bin".to_string());
+        assert!(!report.is_clean());
+    }
+}
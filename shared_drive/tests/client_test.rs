@@ -1,7 +1,6 @@
 //! Tests for SharedDriveClient with mocked HTTP responses.
 
-#[allow(unused_imports)]
-use mockito::Server;
+use mockito::{Matcher, Server};
 use serde_json::json;
 use share_drive::models::{FileListResponse, FileMetadata, ServiceAccountCredentials};
 use share_drive::Authenticator;
@@ -30,6 +29,22 @@ mod models {
         assert_eq!(metadata.size, Some(2048));
     }
 
+    #[test]
+    fn test_file_metadata_md5_checksum() {
+        let json = json!({
+            "id": "file123",
+            "name": "document.pdf",
+            "md5Checksum": "d41d8cd98f00b204e9800998ecf8427e"
+        });
+
+        let metadata: FileMetadata = serde_json::from_value(json).unwrap();
+
+        assert_eq!(
+            metadata.md5_checksum,
+            Some("d41d8cd98f00b204e9800998ecf8427e".to_string())
+        );
+    }
+
     #[test]
     fn test_file_metadata_without_size() {
         let json = json!({
@@ -106,6 +121,19 @@ mod credentials {
         assert!(auth.is_ok());
     }
 
+    #[test]
+    fn test_authenticator_from_json_str() {
+        let creds_json = json!({
+            "type": "service_account",
+            "client_email": "test@project.iam.gserviceaccount.com",
+            "private_key": "key"
+        });
+
+        let auth = Authenticator::from_json_str(&creds_json.to_string()).unwrap();
+        assert_eq!(auth.client_email(), "test@project.iam.gserviceaccount.com");
+        assert!(Authenticator::from_json_str("not valid json").is_err());
+    }
+
     #[test]
     fn test_authenticator_from_invalid_file() {
         let auth = Authenticator::from_file("/nonexistent/path/credentials.json");
@@ -124,6 +152,7 @@ mod credentials {
 
 mod error_handling {
     use share_drive::error::DriveError;
+    use std::io::Write;
 
     #[test]
     fn test_error_display() {
@@ -137,12 +166,57 @@ mod error_handling {
         assert!(display.contains("File not found"));
     }
 
+    #[test]
+    fn test_rate_limited_error() {
+        let err = DriveError::RateLimited {
+            retry_after: Some(std::time::Duration::from_secs(5)),
+            message: "User Rate Limit Exceeded".to_string(),
+        };
+        assert!(err.is_rate_limited());
+        assert!(format!("{}", err).contains("User Rate Limit Exceeded"));
+
+        let err = DriveError::ApiError {
+            status: 500,
+            message: "Backend Error".to_string(),
+        };
+        assert!(!err.is_rate_limited());
+    }
+
+    #[test]
+    fn test_error_from_response_body() {
+        let body = r#"{"error": {"code": 404, "message": "File not found: abc"}}"#;
+        match DriveError::from_response_body(404, body) {
+            DriveError::ApiError { status, message } => {
+                assert_eq!(status, 404);
+                assert_eq!(message, "File not found: abc");
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        match DriveError::from_response_body(502, "Bad Gateway") {
+            DriveError::ApiError { status, message } => {
+                assert_eq!(status, 502);
+                assert_eq!(message, "Bad Gateway");
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
     #[test]
     fn test_invalid_url_error() {
         let err = DriveError::InvalidUrlOrId("bad-url".to_string());
         let display = format!("{}", err);
         assert!(display.contains("bad-url"));
     }
+    #[test]
+    fn test_invalid_certificate_error() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "not a certificate").unwrap();
+        match share_drive::client::read_certificates(file.path()) {
+            Err(DriveError::InvalidCertificate { .. }) => {}
+            other => panic!("unexpected result: {:?}", other.map(|certs| certs.len())),
+        }
+    }
 }
 
 mod file_metadata_display {
@@ -156,6 +230,8 @@ mod file_metadata_display {
             mime_type: Some("application/pdf".to_string()),
             web_view_link: Some("https://example.com".to_string()),
             size: Some(1048576), // 1 MB
+            md5_checksum: None,
+            ..Default::default()
         };
 
         let display = format!("{}", metadata);
@@ -173,6 +249,8 @@ mod file_metadata_display {
             mime_type: Some("application/vnd.google-apps.folder".to_string()),
             web_view_link: None,
             size: None,
+            md5_checksum: None,
+            ..Default::default()
         };
 
         let display = format!("{}", metadata);
@@ -181,3 +259,1293 @@ mod file_metadata_display {
         assert!(display.contains("-")); // No size
     }
 }
+
+mod conflict_policy {
+    use share_drive::client::conflict_name;
+    use share_drive::{ConflictPolicy, UploadOptions};
+
+    #[test]
+    fn test_default_is_overwrite() {
+        assert_eq!(ConflictPolicy::default(), ConflictPolicy::Overwrite);
+        assert_eq!(UploadOptions::default().on_conflict, ConflictPolicy::Overwrite);
+    }
+
+    #[test]
+    fn test_conflict_name_with_extension() {
+        assert_eq!(conflict_name("report.pdf", 1), "report (1).pdf");
+        assert_eq!(conflict_name("archive.tar.gz", 2), "archive.tar (2).gz");
+    }
+
+    #[test]
+    fn test_conflict_name_without_extension() {
+        assert_eq!(conflict_name("Makefile", 1), "Makefile (1)");
+        assert_eq!(conflict_name(".env", 3), ".env (3)");
+    }
+}
+
+mod chunk_size {
+    use share_drive::client::{upload_chunk_size, MAX_CHUNK_SIZE};
+
+    #[test]
+    fn test_rounds_to_256k_multiple() {
+        assert_eq!(upload_chunk_size(16 * 1024 * 1024), 16 * 1024 * 1024);
+        assert_eq!(upload_chunk_size(1_000_000), 768 * 1024);
+        assert_eq!(upload_chunk_size(0), 256 * 1024);
+        assert_eq!(upload_chunk_size(u64::MAX), MAX_CHUNK_SIZE);
+    }
+}
+
+mod list_options {
+    use share_drive::ListOptions;
+
+    #[test]
+    fn test_default_page_size_is_server_default() {
+        assert_eq!(ListOptions::default().effective_page_size(), None);
+    }
+
+    #[test]
+    fn test_page_size_follows_small_max_results() {
+        let options = ListOptions {
+            max_results: Some(20),
+            ..ListOptions::default()
+        };
+        assert_eq!(options.effective_page_size(), Some(20));
+
+        let options = ListOptions {
+            max_results: Some(50_000),
+            ..ListOptions::default()
+        };
+        assert_eq!(options.effective_page_size(), Some(1000));
+    }
+
+    #[test]
+    fn test_explicit_page_size_is_clamped() {
+        let options = ListOptions {
+            page_size: Some(5000),
+            max_results: Some(20),
+            ..ListOptions::default()
+        };
+        assert_eq!(options.effective_page_size(), Some(1000));
+
+        let options = ListOptions {
+            page_size: Some(0),
+            ..ListOptions::default()
+        };
+        assert_eq!(options.effective_page_size(), Some(1));
+    }
+}
+
+mod download_ranges {
+    use share_drive::client::split_ranges;
+    use share_drive::DownloadOptions;
+
+    #[test]
+    fn test_default_single_connection() {
+        assert_eq!(DownloadOptions::default().connections, 1);
+    }
+
+    #[test]
+    fn test_split_even() {
+        assert_eq!(split_ranges(100, 4), vec![(0, 24), (25, 49), (50, 74), (75, 99)]);
+    }
+
+    #[test]
+    fn test_split_with_remainder() {
+        assert_eq!(split_ranges(10, 3), vec![(0, 3), (4, 6), (7, 9)]);
+    }
+
+    #[test]
+    fn test_split_more_parts_than_bytes() {
+        assert_eq!(split_ranges(2, 8), vec![(0, 0), (1, 1)]);
+        assert!(split_ranges(0, 4).is_empty());
+    }
+}
+
+mod download_resume {
+    use share_drive::client::part_path;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_part_path_appends_suffix() {
+        assert_eq!(part_path(Path::new("out/video.mp4")), PathBuf::from("out/video.mp4.part"));
+        assert_eq!(part_path(Path::new("README")), PathBuf::from("README.part"));
+    }
+}
+
+mod file_names {
+    use share_drive::client::is_safe_file_name;
+
+    #[test]
+    fn test_rejects_names_leaving_the_folder() {
+        assert!(is_safe_file_name("report.pdf"));
+        assert!(is_safe_file_name("..hidden"));
+        assert!(!is_safe_file_name("../escape.txt"));
+        assert!(!is_safe_file_name("a/b.txt"));
+        assert!(!is_safe_file_name("..\\escape.txt"));
+        assert!(!is_safe_file_name(".."));
+        assert!(!is_safe_file_name("."));
+        assert!(!is_safe_file_name(""));
+    }
+}
+
+mod mocked_api {
+    use super::*;
+    use futures::StreamExt;
+    use share_drive::{
+        BatchProgress, ConflictPolicy, DownloadOptions, DriveError, ListOptions, MetadataCache,
+        ProgressCallback, RetryPolicy, SharedDriveClient, TransferEvent, TrashFilter,
+        UploadAction, UploadOptions, UploadOutcome,
+    };
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// User credentials whose token requests go to `server`.
+    async fn authenticator(server: &mut Server) -> Authenticator {
+        server
+            .mock("POST", "/token")
+            .with_body(
+                json!({"access_token": "ya29.test", "token_type": "Bearer", "expires_in": 3600})
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+        let credentials = json!({
+            "type": "authorized_user",
+            "client_id": "id",
+            "client_secret": "secret",
+            "refresh_token": "1//refresh"
+        });
+        Authenticator::from_json_str(&credentials.to_string())
+            .unwrap()
+            .with_token_uri(format!("{}/token", server.url()))
+    }
+
+    /// A My Drive client whose token and API requests all go to `server`.
+    async fn client(server: &mut Server) -> SharedDriveClient {
+        let auth = authenticator(server).await;
+        SharedDriveClient::my_drive(auth).with_base_urls(
+            format!("{}/drive/v3", server.url()),
+            format!("{}/upload/drive/v3", server.url()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_list_files() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        let mock = server
+            .mock("GET", "/drive/v3/files")
+            .match_header("authorization", "Bearer ya29.test")
+            .match_query(Matcher::UrlEncoded(
+                "q".into(),
+                "'folder1' in parents and trashed = false".into(),
+            ))
+            .with_body(json!({"files": [{"id": "f1", "name": "a.txt"}]}).to_string())
+            .create_async()
+            .await;
+
+        let files = client.list_files("folder1").await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "a.txt");
+        mock.assert_async().await;
+
+        let stats = client.stats();
+        assert_eq!(stats.api_calls(), 1);
+        assert_eq!(stats.endpoints()["GET /files"].calls, 1);
+        assert_eq!(stats.retries(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_find_file_escapes_name() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        let query = r"name = 'Bob\'s \\ notes.txt' and 'folder1' in parents and trashed = false";
+        let mock = server
+            .mock("GET", "/drive/v3/files")
+            .match_query(Matcher::UrlEncoded("q".into(), query.into()))
+            .with_body(
+                json!({"files": [
+                    {"id": "f1", "name": r"Bob's \ notes.txt"},
+                    {"id": "f2", "name": r"bob's \ NOTES.txt"}
+                ]})
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let file = client.find_file(r"Bob's \ notes.txt", "folder1").await.unwrap();
+        assert_eq!(file.unwrap().id, "f1");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_list_trashed_files() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        let only = server
+            .mock("GET", "/drive/v3/files")
+            .match_query(Matcher::UrlEncoded(
+                "q".into(),
+                "'folder1' in parents and trashed = true".into(),
+            ))
+            .with_body(
+                json!({"files": [{"id": "f1", "name": "old.txt", "trashed": true}]}).to_string(),
+            )
+            .create_async()
+            .await;
+        let include = server
+            .mock("GET", "/drive/v3/files")
+            .match_query(Matcher::UrlEncoded("q".into(), "'folder1' in parents".into()))
+            .with_body(json!({"files": []}).to_string())
+            .create_async()
+            .await;
+
+        let options = ListOptions {
+            trash: TrashFilter::Only,
+            ..ListOptions::default()
+        };
+        let files = client.list_files_with_options("folder1", &options).await.unwrap();
+        assert!(files[0].trashed);
+        let options = ListOptions {
+            trash: TrashFilter::Include,
+            ..ListOptions::default()
+        };
+        client.list_files_with_options("folder1", &options).await.unwrap();
+        only.assert_async().await;
+        include.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_file_uses_metadata_cache() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server)
+            .await
+            .with_metadata_cache(MetadataCache::new(Duration::from_secs(60)));
+        let mock = server
+            .mock("GET", "/drive/v3/files/f1")
+            .match_query(Matcher::Any)
+            .with_body(json!({"id": "f1", "name": "a.txt"}).to_string())
+            .expect(1)
+            .create_async()
+            .await;
+
+        assert_eq!(client.get_file("f1").await.unwrap().name, "a.txt");
+        assert_eq!(client.get_file("f1").await.unwrap().name, "a.txt");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_resource_keys_are_sent() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        let mock = server
+            .mock("GET", "/drive/v3/files/f1")
+            .match_query(Matcher::Any)
+            .match_header("x-goog-drive-resource-keys", "f1/0-abc,f2/0-def")
+            .with_body(json!({"id": "f1", "name": "a.txt"}).to_string())
+            .create_async()
+            .await;
+
+        client.add_resource_key("f2", "0-def");
+        client.add_resource_key("f1", "0-abc");
+        assert_eq!(client.get_file("f1").await.unwrap().name, "a.txt");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_delete_missing_file_succeeds() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        server
+            .mock("DELETE", "/drive/v3/files/gone")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .create_async()
+            .await;
+
+        assert!(client.delete_file("gone").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_empty_trash_of_shared_drive() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await.with_drive_id("d1".to_string());
+        let mock = server
+            .mock("DELETE", "/drive/v3/files/trash")
+            .match_query(Matcher::UrlEncoded("driveId".into(), "d1".into()))
+            .with_status(204)
+            .create_async()
+            .await;
+
+        client.empty_trash().await.unwrap();
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_delete_files_in_batches() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        let part = |n: usize, status: &str| {
+            format!(
+                "--resp\r\nContent-Type: application/http\r\nContent-ID: <response-item{}>\r\n\r\n\
+                 HTTP/1.1 {}\r\n\r\n\r\n",
+                n, status
+            )
+        };
+        let full: String = (1..=100).map(|n| part(n, "204 No Content")).collect();
+        let rest = part(1, "204 No Content")
+            + &part(2, "404 Not Found")
+            + "--resp\r\nContent-ID: <response-item3>\r\n\r\nHTTP/1.1 403 Forbidden\r\n\r\n\
+               {\"error\": {\"code\": 403, \"message\": \"Insufficient permissions\"}}\r\n";
+        let first = server
+            .mock("POST", "/batch/drive/v3")
+            .match_header("authorization", "Bearer ya29.test")
+            .match_body(Matcher::Regex("DELETE /drive/v3/files/id0\\?".into()))
+            .with_header("content-type", "multipart/mixed; boundary=resp")
+            .with_body(full + "--resp--\r\n")
+            .create_async()
+            .await;
+        let second = server
+            .mock("POST", "/batch/drive/v3")
+            .match_body(Matcher::Regex("DELETE /drive/v3/files/id100\\?".into()))
+            .with_header("content-type", "multipart/mixed; boundary=resp")
+            .with_body(rest + "--resp--\r\n")
+            .create_async()
+            .await;
+
+        let ids: Vec<String> = (0..103).map(|n| format!("id{}", n)).collect();
+        let results = client.delete_files(&ids).await;
+        assert_eq!(results.len(), 103);
+        assert!(results[..102].iter().all(|r| r.is_ok()));
+        assert!(matches!(results[102], Err(DriveError::ApiError { status: 403, .. })));
+        first.assert_async().await;
+        second.assert_async().await;
+        assert_eq!(client.stats().endpoints()["POST batch"].calls, 2);
+    }
+
+    #[tokio::test]
+    async fn test_delete_files_retries_parts_and_keeps_earlier_results() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await.with_retry_policy(RetryPolicy {
+            max_attempts: 2,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+        });
+        let part = |n: usize, status: &str| {
+            format!(
+                "--resp\r\nContent-ID: <response-item{}>\r\n\r\nHTTP/1.1 {}\r\n\r\n\r\n",
+                n, status
+            )
+        };
+        let first_body: String = part(1, "503 Service Unavailable")
+            + &(2..=100).map(|n| part(n, "204 No Content")).collect::<String>()
+            + "--resp--\r\n";
+        let first = server
+            .mock("POST", "/batch/drive/v3")
+            .match_body(Matcher::Regex("DELETE /drive/v3/files/id99\\?".into()))
+            .with_header("content-type", "multipart/mixed; boundary=resp")
+            .with_body(first_body)
+            .create_async()
+            .await;
+        // Only id0 is sent again, as the single part of its batch
+        let retried = server
+            .mock("POST", "/batch/drive/v3")
+            .match_body(Matcher::Regex("files/id0\\?[^\r]*\r\n\r\n--batch_[0-9a-f]+--".into()))
+            .with_header("content-type", "multipart/mixed; boundary=resp")
+            .with_body(part(1, "204 No Content") + "--resp--\r\n")
+            .create_async()
+            .await;
+        let rejected = server
+            .mock("POST", "/batch/drive/v3")
+            .match_body(Matcher::Regex("DELETE /drive/v3/files/id100\\?".into()))
+            .with_status(400)
+            .with_body(json!({"error": {"code": 400, "message": "Bad batch"}}).to_string())
+            .create_async()
+            .await;
+
+        let ids: Vec<String> = (0..101).map(|n| format!("id{}", n)).collect();
+        let results = client.delete_files(&ids).await;
+        assert!(results[..100].iter().all(|r| r.is_ok()));
+        assert!(matches!(
+            &results[100],
+            Err(DriveError::BatchRequestFailed(message)) if message.contains("Bad batch")
+        ));
+        first.assert_async().await;
+        retried.assert_async().await;
+        rejected.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_files_batches_uncached() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server)
+            .await
+            .with_metadata_cache(MetadataCache::new(Duration::from_secs(60)));
+        server
+            .mock("GET", "/drive/v3/files/f1")
+            .match_query(Matcher::Any)
+            .with_body(json!({"id": "f1", "name": "a.txt"}).to_string())
+            .create_async()
+            .await;
+        client.get_file("f1").await.unwrap();
+
+        let body = "--resp\r\nContent-ID: <response-item1>\r\n\r\n\
+                    HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n\
+                    {\"id\": \"f2\", \"name\": \"b.txt\"}\r\n\
+                    --resp\r\nContent-ID: <response-item2>\r\n\r\n\
+                    HTTP/1.1 404 Not Found\r\n\r\n\
+                    {\"error\": {\"code\": 404, \"message\": \"File not found: f3\"}}\r\n\
+                    --resp--\r\n";
+        let mock = server
+            .mock("POST", "/batch/drive/v3")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::Regex("GET /drive/v3/files/f2\\?supportsAllDrives=true&fields=".into()),
+                Matcher::Regex("GET /drive/v3/files/f3\\?".into()),
+            ]))
+            .with_header("content-type", "multipart/mixed; boundary=resp")
+            .with_body(body)
+            .create_async()
+            .await;
+
+        let files = client.get_files(&["f1", "f2", "f3"]).await.unwrap();
+        assert_eq!(files[0].as_ref().unwrap().name, "a.txt");
+        assert_eq!(files[1].as_ref().unwrap().name, "b.txt");
+        assert!(files[2].as_ref().unwrap_err().is_not_found());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_generate_ids() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        let mock = server
+            .mock("GET", "/drive/v3/files/generateIds")
+            .match_query(Matcher::UrlEncoded("count".into(), "2".into()))
+            .with_body(json!({"kind": "drive#generatedIds", "ids": ["id1", "id2"]}).to_string())
+            .create_async()
+            .await;
+
+        assert_eq!(client.generate_ids(2).await.unwrap(), ["id1", "id2"]);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_upload_with_existing_id_is_skipped() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        server
+            .mock("GET", "/drive/v3/files/pregenerated")
+            .match_query(Matcher::Any)
+            .with_body(json!({"id": "pregenerated", "name": "a.txt"}).to_string())
+            .create_async()
+            .await;
+        let upload = server
+            .mock("POST", "/upload/drive/v3/files")
+            .match_query(Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "hello").unwrap();
+        let options = UploadOptions {
+            file_id: Some("pregenerated".to_string()),
+            ..UploadOptions::default()
+        };
+        let outcome = client
+            .upload_file_with_options(&path, "folder1", &options, None)
+            .await
+            .unwrap();
+        assert!(matches!(outcome, UploadOutcome::Skipped(f) if f.id == "pregenerated"));
+        upload.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_set_starred() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        let mock = server
+            .mock("PATCH", "/drive/v3/files/f1")
+            .match_query(Matcher::Any)
+            .match_body(Matcher::Json(json!({"starred": true})))
+            .with_body(json!({"id": "f1", "name": "a.txt", "starred": true}).to_string())
+            .create_async()
+            .await;
+
+        assert!(client.set_starred("f1", true).await.unwrap().starred);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_comments() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        let first_page = server
+            .mock("GET", "/drive/v3/files/f1/comments")
+            .match_query(Matcher::Regex("fields=nextPageToken".into()))
+            .with_body(
+                json!({
+                    "comments": [{
+                        "id": "c1",
+                        "content": "Looks good",
+                        "author": {"displayName": "Alice"},
+                        "replies": [{"id": "r1", "content": "Thanks"}],
+                    }],
+                    "nextPageToken": "p2",
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        let second_page = server
+            .mock("GET", "/drive/v3/files/f1/comments")
+            .match_query(Matcher::UrlEncoded("pageToken".into(), "p2".into()))
+            .with_body(json!({"comments": [{"id": "c2", "resolved": true}]}).to_string())
+            .create_async()
+            .await;
+        let create = server
+            .mock("POST", "/drive/v3/files/f1/comments")
+            .match_query(Matcher::Any)
+            .match_body(Matcher::Json(json!({"content": "Please fix page 2"})))
+            .with_body(json!({"id": "c3", "content": "Please fix page 2"}).to_string())
+            .create_async()
+            .await;
+
+        let comments = client.list_comments("f1").await.unwrap();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].replies[0].content, "Thanks");
+        assert!(comments[1].resolved);
+        let comment = client.create_comment("f1", "Please fix page 2").await.unwrap();
+        assert_eq!(comment.id, "c3");
+        first_page.assert_async().await;
+        second_page.assert_async().await;
+        create.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_set_description_and_folder_color() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        let description = server
+            .mock("PATCH", "/drive/v3/files/f1")
+            .match_query(Matcher::Any)
+            .match_body(Matcher::Json(json!({"description": "Drop zone"})))
+            .with_body(json!({"id": "f1", "name": "in", "description": "Drop zone"}).to_string())
+            .create_async()
+            .await;
+        let color = server
+            .mock("PATCH", "/drive/v3/files/f1")
+            .match_query(Matcher::Any)
+            .match_body(Matcher::Json(json!({"folderColorRgb": "#4285F4"})))
+            .with_body(json!({"id": "f1", "name": "in", "folderColorRgb": "#4285f4"}).to_string())
+            .create_async()
+            .await;
+
+        let file = client.set_description("f1", "Drop zone").await.unwrap();
+        assert_eq!(file.description.as_deref(), Some("Drop zone"));
+        let folder = client.set_folder_color("f1", "#4285F4").await.unwrap();
+        assert_eq!(folder.folder_color_rgb.as_deref(), Some("#4285f4"));
+        description.assert_async().await;
+        color.assert_async().await;
+
+        for invalid in ["4285F4", "#4285F", "#GGGGGG"] {
+            let err = client.set_folder_color("f1", invalid).await.unwrap_err();
+            assert!(matches!(err, DriveError::InvalidColor(_)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trash_file_and_create_shortcut() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        let trash = server
+            .mock("PATCH", "/drive/v3/files/f2")
+            .match_query(Matcher::Any)
+            .match_body(Matcher::Json(json!({"trashed": true})))
+            .with_body(json!({"id": "f2", "name": "copy.txt", "trashed": true}).to_string())
+            .create_async()
+            .await;
+        let shortcut = server
+            .mock("POST", "/drive/v3/files")
+            .match_query(Matcher::Any)
+            .match_body(Matcher::PartialJson(json!({
+                "name": "copy.txt",
+                "mimeType": "application/vnd.google-apps.shortcut",
+                "parents": ["folder1"],
+                "shortcutDetails": {"targetId": "f1"},
+            })))
+            .with_body(json!({"id": "s1", "name": "copy.txt"}).to_string())
+            .create_async()
+            .await;
+
+        assert!(client.trash_file("f2").await.unwrap().trashed);
+        let created = client.create_shortcut("f1", "folder1", "copy.txt").await.unwrap();
+        assert_eq!(created.id, "s1");
+        trash.assert_async().await;
+        shortcut.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_recent_files() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        let mock = server
+            .mock("GET", "/drive/v3/files")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("orderBy".into(), "modifiedTime desc".into()),
+                Matcher::UrlEncoded("pageSize".into(), "5".into()),
+            ]))
+            .with_body(json!({"files": [{"id": "f2", "name": "new.txt"}]}).to_string())
+            .create_async()
+            .await;
+
+        let files = client.recent_files(5).await.unwrap();
+        assert_eq!(files[0].id, "f2");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_plan_upload_changes_nothing() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "hello").unwrap();
+        // Only the lookup is mocked, so any upload or delete would fail
+        server
+            .mock("GET", "/drive/v3/files")
+            .match_query(Matcher::Any)
+            .with_body(
+                json!({"files": [{
+                    "id": "f1",
+                    "name": "a.txt",
+                    "md5Checksum": "5d41402abc4b2a76b9719d911017c592"
+                }]})
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let options = UploadOptions {
+            skip_identical: true,
+            ..UploadOptions::default()
+        };
+        let action = client.plan_upload(&path, "folder1", &options).await.unwrap();
+        assert!(matches!(action, UploadAction::Skip(ref f) if f.id == "f1"), "{:?}", action);
+
+        let options = UploadOptions::default();
+        let action = client.plan_upload(&path, "folder1", &options).await.unwrap();
+        assert!(matches!(action, UploadAction::Replace(ref f) if f.id == "f1"), "{:?}", action);
+
+        let options = UploadOptions {
+            on_conflict: ConflictPolicy::Error,
+            ..UploadOptions::default()
+        };
+        let err = client.plan_upload(&path, "folder1", &options).await.unwrap_err();
+        assert!(matches!(err, DriveError::FileExists { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_upload_checksum_mismatch() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "hello").unwrap();
+        server
+            .mock("GET", "/drive/v3/files")
+            .match_query(Matcher::Any)
+            .with_body(json!({"files": []}).to_string())
+            .create_async()
+            .await;
+        let mut uploaded = |md5: &str| {
+            server
+                .mock("POST", "/upload/drive/v3/files")
+                .match_query(Matcher::UrlEncoded("uploadType".into(), "multipart".into()))
+                .with_body(json!({"id": "f1", "name": "a.txt", "md5Checksum": md5}).to_string())
+                .expect(1)
+        };
+
+        let _intact = uploaded("5d41402abc4b2a76b9719d911017c592").create_async().await;
+        let outcome = client
+            .upload_file_with_options(&path, "folder1", &UploadOptions::default(), None)
+            .await
+            .unwrap();
+        assert!(matches!(outcome, UploadOutcome::Uploaded(ref f) if f.id == "f1"));
+
+        let _corrupted = uploaded("d41d8cd98f00b204e9800998ecf8427e").create_async().await;
+        let err = client
+            .upload_file_with_options(&path, "folder1", &UploadOptions::default(), None)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, DriveError::ChecksumMismatch { ref actual, .. }
+                if actual == "d41d8cd98f00b204e9800998ecf8427e"),
+            "{:?}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retried_upload_counts_bytes_once() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await.with_retry_policy(RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "hello").unwrap();
+        server
+            .mock("GET", "/drive/v3/files")
+            .match_query(Matcher::Any)
+            .with_body(json!({"files": []}).to_string())
+            .create_async()
+            .await;
+        let unavailable = server
+            .mock("POST", "/upload/drive/v3/files")
+            .match_query(Matcher::Any)
+            .with_status(503)
+            .expect(1)
+            .create_async()
+            .await;
+        let uploaded = server
+            .mock("POST", "/upload/drive/v3/files")
+            .match_query(Matcher::Any)
+            .with_body(
+                json!({
+                    "id": "f1",
+                    "name": "a.txt",
+                    "md5Checksum": "5d41402abc4b2a76b9719d911017c592",
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        client
+            .upload_file_with_options(&path, "folder1", &UploadOptions::default(), None)
+            .await
+            .unwrap();
+        unavailable.assert_async().await;
+        uploaded.assert_async().await;
+        assert_eq!(client.stats().uploaded_bytes(), 5);
+        assert_eq!(client.stats().retries(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_upload_many_stream() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        let dir = tempfile::tempdir().unwrap();
+        let paths = [dir.path().join("a.txt"), dir.path().join("b.txt")];
+        for path in &paths {
+            std::fs::write(path, "hello").unwrap();
+        }
+        server
+            .mock("GET", "/drive/v3/files")
+            .match_query(Matcher::Any)
+            .with_body(json!({"files": []}).to_string())
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/upload/drive/v3/files")
+            .match_query(Matcher::Any)
+            .with_body(
+                json!({
+                    "id": "f1",
+                    "name": "a.txt",
+                    "md5Checksum": "5d41402abc4b2a76b9719d911017c592",
+                })
+                .to_string(),
+            )
+            .expect(2)
+            .create_async()
+            .await;
+
+        let batch = BatchProgress::new(vec![5, 5], Arc::new(|_| {}));
+        let started = Mutex::new(Vec::new());
+        let file_progress = |idx: usize| -> ProgressCallback {
+            started.lock().unwrap().push(idx);
+            Arc::new(|_| {})
+        };
+        let options = UploadOptions::default();
+        let results: Vec<_> = client
+            .upload_many_stream(&paths, "folder1", &options, 2, Some(&batch), Some(&file_progress))
+            .collect()
+            .await;
+
+        let mut indices: Vec<usize> = results.iter().map(|(idx, _)| *idx).collect();
+        indices.sort();
+        assert_eq!(indices, [0, 1]);
+        assert!(results.iter().all(|(_, r)| matches!(r, Ok(UploadOutcome::Uploaded(_)))));
+        started.lock().unwrap().sort();
+        assert_eq!(*started.lock().unwrap(), [0, 1]);
+        assert_eq!(batch.snapshot().bytes_transferred, 10);
+    }
+
+    #[tokio::test]
+    async fn test_download_rejects_unsafe_name() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        server
+            .mock("GET", "/drive/v3/files/f1")
+            .match_query(Matcher::Any)
+            .with_body(json!({"id": "f1", "name": "../escape.txt", "size": "5"}).to_string())
+            .create_async()
+            .await;
+        let dir = tempfile::tempdir().unwrap();
+        let to = dir.path().join("to");
+        std::fs::create_dir(&to).unwrap();
+
+        let err = client
+            .download_file_with_options("f1", &to, &DownloadOptions::default(), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DriveError::UnsafeFileName(ref name) if name == "../escape.txt"));
+        assert!(!dir.path().join("escape.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_checksum_mismatch_removes_part_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let part = dir.path().join("a.txt.part");
+        std::fs::write(&part, "file ").unwrap();
+
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        server
+            .mock("GET", "/drive/v3/files/f1")
+            .match_query(Matcher::UrlEncoded("alt".into(), "media".into()))
+            .match_header("range", "bytes=5-")
+            .with_status(206)
+            .with_body("content")
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/drive/v3/files/f1")
+            .match_query(Matcher::Regex("fields=".into()))
+            .with_body(
+                json!({
+                    "id": "f1",
+                    "name": "a.txt",
+                    "size": "12",
+                    "md5Checksum": "d41d8cd98f00b204e9800998ecf8427e",
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let options = DownloadOptions {
+            resume: true,
+            ..DownloadOptions::default()
+        };
+        let err = client
+            .download_file_with_options("f1", dir.path(), &options, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DriveError::ChecksumMismatch { .. }), "{:?}", err);
+        assert!(!part.exists());
+        assert!(!dir.path().join("a.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_download_removes_part_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let part = dir.path().join("a.txt.part");
+
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        // Trickles out 200 KiB over two seconds, stopping once the client hangs up
+        server
+            .mock("GET", "/drive/v3/files/f1")
+            .match_query(Matcher::UrlEncoded("alt".into(), "media".into()))
+            .with_chunked_body(|w| {
+                for _ in 0..200 {
+                    w.write_all(&[0; 1024])?;
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                Ok(())
+            })
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/drive/v3/files/f1")
+            .match_query(Matcher::Regex("fields=".into()))
+            .with_body(json!({"id": "f1", "name": "a.txt", "size": "204800"}).to_string())
+            .create_async()
+            .await;
+
+        for resume in [false, true] {
+            let options = DownloadOptions {
+                resume,
+                ..DownloadOptions::default()
+            };
+            // Cancel the download once data has been written
+            let started = Arc::new(tokio::sync::Notify::new());
+            let notify = Arc::clone(&started);
+            let progress: ProgressCallback = Arc::new(move |_| notify.notify_one());
+            let download =
+                client.download_file_with_options("f1", dir.path(), &options, Some(progress));
+            tokio::select! {
+                _ = download => panic!("download should still be running"),
+                _ = started.notified() => {}
+            }
+            assert_eq!(part.exists(), resume);
+            assert!(!dir.path().join("a.txt").exists());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upload_from_reader() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await.with_chunk_size(256 * 1024);
+        let session = format!("{}/upload/session1", server.url());
+        server
+            .mock("POST", "/upload/drive/v3/files")
+            .match_query(Matcher::UrlEncoded("uploadType".into(), "resumable".into()))
+            .match_header("x-upload-content-length", "307200")
+            .match_body(Matcher::PartialJson(json!({"name": "backup.tar.gz"})))
+            .with_header("location", &session)
+            .create_async()
+            .await;
+        let first = server
+            .mock("PUT", "/upload/session1")
+            .match_header("content-range", "bytes 0-262143/307200")
+            .with_status(308)
+            .create_async()
+            .await;
+        let last = server
+            .mock("PUT", "/upload/session1")
+            .match_header("content-range", "bytes 262144-307199/307200")
+            .with_body(json!({"id": "f1", "name": "backup.tar.gz"}).to_string())
+            .create_async()
+            .await;
+
+        let data = vec![7u8; 300 * 1024];
+        let file = client
+            .upload_from_reader(&mut data.as_slice(), 307200, "backup.tar.gz", "folder1", None)
+            .await
+            .unwrap();
+        assert_eq!(file.id, "f1");
+        first.assert_async().await;
+        last.assert_async().await;
+        assert_eq!(client.stats().uploaded_bytes(), 307200);
+
+        // A reader that runs dry before the promised size
+        let err = client
+            .upload_from_reader(&mut &data[..1000], 307200, "backup.tar.gz", "folder1", None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DriveError::InputReadError(_)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_upload_from_reader_resends_unconfirmed_bytes() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server)
+            .await
+            .with_chunk_size(256 * 1024)
+            .with_retry_policy(RetryPolicy {
+                base_delay: Duration::from_millis(1),
+                ..RetryPolicy::default()
+            });
+        let session = format!("{}/upload/session1", server.url());
+        server
+            .mock("POST", "/upload/drive/v3/files")
+            .match_query(Matcher::UrlEncoded("uploadType".into(), "resumable".into()))
+            .with_header("location", &session)
+            .create_async()
+            .await;
+        let mut put = |range: &str| {
+            server
+                .mock("PUT", "/upload/session1")
+                .match_header("content-range", range)
+                .expect(1)
+        };
+        // Only half of the first chunk is kept
+        let first = put("bytes 0-262143/307200")
+            .with_status(308)
+            .with_header("range", "bytes=0-131071")
+            .create_async()
+            .await;
+        // Resending the rest fails after a quarter of the chunk arrived
+        let failed = put("bytes 131072-262143/307200").with_status(503).create_async().await;
+        let status = put("bytes */307200")
+            .with_status(308)
+            .with_header("range", "bytes=0-196607")
+            .create_async()
+            .await;
+        let resent = put("bytes 196608-262143/307200").with_status(308).create_async().await;
+        let last = put("bytes 262144-307199/307200")
+            .with_body(json!({"id": "f1", "name": "backup.tar.gz"}).to_string())
+            .create_async()
+            .await;
+
+        let data = vec![7u8; 300 * 1024];
+        let file = client
+            .upload_from_reader(&mut data.as_slice(), 307200, "backup.tar.gz", "folder1", None)
+            .await
+            .unwrap();
+        assert_eq!(file.id, "f1");
+        for mock in [first, failed, status, resent, last] {
+            mock.assert_async().await;
+        }
+        assert_eq!(client.stats().uploaded_bytes(), 307200);
+    }
+
+    #[tokio::test]
+    async fn test_download_to_writer() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await;
+        server
+            .mock("GET", "/drive/v3/files/f1")
+            .match_query(Matcher::UrlEncoded("alt".into(), "media".into()))
+            .with_body("file content")
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/drive/v3/files/f1")
+            .match_query(Matcher::Regex("fields=".into()))
+            .with_body(json!({"id": "f1", "name": "a.txt", "size": "12"}).to_string())
+            .create_async()
+            .await;
+
+        let mut buffer = Vec::new();
+        let file = client.download_to_writer("f1", &mut buffer, None).await.unwrap();
+        assert_eq!(file.name, "a.txt");
+        assert_eq!(buffer, b"file content");
+    }
+
+    #[tokio::test]
+    async fn test_resumable_upload_continues_after_failed_chunk() {
+        const MIB: u64 = 1024 * 1024;
+        let mut server = Server::new_async().await;
+        let client = client(&mut server)
+            .await
+            .with_chunk_size(32 * MIB)
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 2,
+                base_delay: Duration::from_millis(1),
+                ..RetryPolicy::default()
+            });
+        // Just over the size from which uploads are resumable
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("big.bin");
+        let size = 50 * MIB + 1;
+        std::fs::File::create(&path).unwrap().set_len(size).unwrap();
+
+        server
+            .mock("GET", "/drive/v3/files")
+            .match_query(Matcher::Any)
+            .with_body(json!({"files": []}).to_string())
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/upload/drive/v3/files")
+            .match_query(Matcher::UrlEncoded("uploadType".into(), "resumable".into()))
+            .with_header("location", &format!("{}/upload/session1", server.url()))
+            .create_async()
+            .await;
+        let range = |start: u64, end: u64| format!("bytes {}-{}/{}", start, end, size);
+        let failed = server
+            .mock("PUT", "/upload/session1")
+            .match_header("content-range", range(0, 32 * MIB - 1).as_str())
+            .with_status(503)
+            .expect(1)
+            .create_async()
+            .await;
+        // Only the first 16 MiB of the failed chunk arrived
+        server
+            .mock("PUT", "/upload/session1")
+            .match_header("content-range", format!("bytes */{}", size).as_str())
+            .with_status(308)
+            .with_header("range", &format!("bytes=0-{}", 16 * MIB - 1))
+            .create_async()
+            .await;
+        let resumed = server
+            .mock("PUT", "/upload/session1")
+            .match_header("content-range", range(16 * MIB, 48 * MIB - 1).as_str())
+            .with_status(308)
+            .with_header("range", &format!("bytes=0-{}", 48 * MIB - 1))
+            .create_async()
+            .await;
+        let last = server
+            .mock("PUT", "/upload/session1")
+            .match_header("content-range", range(48 * MIB, size - 1).as_str())
+            .with_body(json!({"id": "f1", "name": "big.bin"}).to_string())
+            .create_async()
+            .await;
+
+        let outcome = client
+            .upload_file_with_options(&path, "folder1", &UploadOptions::default(), None)
+            .await
+            .unwrap();
+        assert!(matches!(outcome, UploadOutcome::Uploaded(ref f) if f.id == "f1"));
+        failed.assert_async().await;
+        resumed.assert_async().await;
+        last.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_resumable_upload_checks_offset_before_resending() {
+        const MIB: u64 = 1024 * 1024;
+        let mut server = Server::new_async().await;
+        let client = client(&mut server)
+            .await
+            .with_chunk_size(64 * MIB)
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 3,
+                base_delay: Duration::from_millis(1),
+                ..RetryPolicy::default()
+            });
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("big.bin");
+        let size = 50 * MIB + 1;
+        std::fs::File::create(&path).unwrap().set_len(size).unwrap();
+
+        server
+            .mock("GET", "/drive/v3/files")
+            .match_query(Matcher::Any)
+            .with_body(json!({"files": []}).to_string())
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/upload/drive/v3/files")
+            .match_query(Matcher::UrlEncoded("uploadType".into(), "resumable".into()))
+            .with_header("location", &format!("{}/upload/session1", server.url()))
+            .create_async()
+            .await;
+        let chunk = server
+            .mock("PUT", "/upload/session1")
+            .match_header("content-range", format!("bytes 0-{}/{}", size - 1, size).as_str())
+            .with_status(503)
+            .expect(1)
+            .create_async()
+            .await;
+        // The whole chunk arrived before the connection failed
+        let status = server
+            .mock("PUT", "/upload/session1")
+            .match_header("content-range", format!("bytes */{}", size).as_str())
+            .with_body(json!({"id": "f1", "name": "big.bin"}).to_string())
+            .expect(1)
+            .create_async()
+            .await;
+
+        let outcome = client
+            .upload_file_with_options(&path, "folder1", &UploadOptions::default(), None)
+            .await
+            .unwrap();
+        assert!(matches!(outcome, UploadOutcome::Uploaded(ref f) if f.id == "f1"));
+        chunk.assert_async().await;
+        status.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_download_events() {
+        let mut server = Server::new_async().await;
+        let client = client(&mut server).await.with_retry_policy(RetryPolicy {
+            base_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        });
+        server
+            .mock("GET", "/drive/v3/files/f1")
+            .match_query(Matcher::UrlEncoded("alt".into(), "media".into()))
+            .with_status(503)
+            .expect(1)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/drive/v3/files/f1")
+            .match_query(Matcher::UrlEncoded("alt".into(), "media".into()))
+            .with_body("file content")
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/drive/v3/files/f1")
+            .match_query(Matcher::Regex("fields=".into()))
+            .with_body(json!({"id": "f1", "name": "a.txt", "size": "12"}).to_string())
+            .create_async()
+            .await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let options = DownloadOptions::default();
+        let events: Vec<_> = client.download_events("f1", dir.path(), &options).collect().await;
+        assert!(matches!(events[0], TransferEvent::Started { total_bytes: 12 }), "{:?}", events);
+        assert!(matches!(events[1], TransferEvent::ChunkRetried { attempt: 2 }), "{:?}", events);
+        assert!(events.iter().any(|e| matches!(e, TransferEvent::Progress(_))));
+        assert!(matches!(events.last(), Some(TransferEvent::Finished(f)) if f.name == "a.txt"));
+        assert_eq!(std::fs::read(dir.path().join("a.txt")).unwrap(), b"file content");
+
+        let events: Vec<_> = client
+            .download_events("f1", "/nonexistent/dir/a.txt", &options)
+            .collect()
+            .await;
+        assert!(matches!(events.last(), Some(TransferEvent::Failed(_))), "{:?}", events);
+    }
+
+    #[tokio::test]
+    async fn test_builder_read_timeout() {
+        let mut server = Server::new_async().await;
+        let auth = authenticator(&mut server).await;
+        server
+            .mock("GET", "/drive/v3/files/slow")
+            .match_query(Matcher::Any)
+            .with_chunked_body(|w| {
+                std::thread::sleep(Duration::from_secs(2));
+                w.write_all(br#"{"id": "slow", "name": "slow.txt"}"#)
+            })
+            .create_async()
+            .await;
+
+        let client = SharedDriveClient::builder(auth)
+            .connect_timeout(Duration::from_secs(5))
+            .read_timeout(Duration::from_millis(200))
+            .build()
+            .unwrap()
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 1,
+                ..RetryPolicy::default()
+            })
+            .with_base_urls(
+                format!("{}/drive/v3", server.url()),
+                format!("{}/upload/drive/v3", server.url()),
+            );
+
+        let err = client.get_file("slow").await.unwrap_err();
+        assert!(matches!(err, DriveError::HttpError(ref e) if e.is_timeout()), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_builder_headers() {
+        let mut server = Server::new_async().await;
+        let auth = authenticator(&mut server).await;
+        let mock = server
+            .mock("GET", "/drive/v3/files/f1")
+            .match_query(Matcher::Any)
+            .match_header("user-agent", "backup-job/2.0")
+            .match_header("x-goog-user-project", "billing-project")
+            .with_body(json!({"id": "f1", "name": "a.txt"}).to_string())
+            .create_async()
+            .await;
+
+        let client = SharedDriveClient::builder(auth)
+            .user_agent("backup-job/2.0")
+            .quota_project("billing-project")
+            .build()
+            .unwrap()
+            .with_base_urls(
+                format!("{}/drive/v3", server.url()),
+                format!("{}/upload/drive/v3", server.url()),
+            );
+        assert_eq!(client.get_file("f1").await.unwrap().name, "a.txt");
+        mock.assert_async().await;
+
+        let auth = authenticator(&mut server).await;
+        let err = SharedDriveClient::builder(auth).quota_project("bad\nproject").build();
+        assert!(matches!(err, Err(DriveError::InvalidHeader { .. })));
+    }
+}
@@ -68,6 +68,66 @@ mod extract_open_url {
     }
 }
 
+mod extract_download_url {
+    use super::*;
+
+    #[test]
+    fn uc_url() {
+        let url = "https://drive.google.com/uc?id=1abc123XYZ&export=download";
+        assert_eq!(extract_id(url).unwrap(), "1abc123XYZ");
+    }
+
+    #[test]
+    fn uc_url_with_id_last() {
+        let url = "https://drive.google.com/uc?export=download&id=1abc123XYZ";
+        assert_eq!(extract_id(url).unwrap(), "1abc123XYZ");
+    }
+
+    #[test]
+    fn usercontent_url() {
+        let url = "https://drive.usercontent.google.com/download?id=1abc123XYZ&export=download&confirm=t";
+        assert_eq!(extract_id(url).unwrap(), "1abc123XYZ");
+    }
+
+    #[test]
+    fn similar_parameter_name() {
+        assert!(extract_id("https://drive.google.com/uc?fileid=1abc123XYZ").is_err());
+    }
+}
+
+mod extract_docs_url {
+    use super::*;
+
+    #[test]
+    fn document_url() {
+        let url = "https://docs.google.com/document/d/1abc123XYZ-_def456/edit";
+        assert_eq!(extract_id(url).unwrap(), "1abc123XYZ-_def456");
+    }
+
+    #[test]
+    fn spreadsheet_url() {
+        let url = "https://docs.google.com/spreadsheets/d/1abc123XYZ/edit#gid=0";
+        assert_eq!(extract_id(url).unwrap(), "1abc123XYZ");
+    }
+
+    #[test]
+    fn presentation_url() {
+        let url = "https://docs.google.com/presentation/d/1abc123XYZ/edit?usp=sharing";
+        assert_eq!(extract_id(url).unwrap(), "1abc123XYZ");
+    }
+
+    #[test]
+    fn url_with_user() {
+        let url = "https://docs.google.com/document/u/0/d/1abc123XYZ/";
+        assert_eq!(extract_id(url).unwrap(), "1abc123XYZ");
+    }
+
+    #[test]
+    fn unknown_editor() {
+        assert!(extract_id("https://docs.google.com/notes/d/1abc123XYZ").is_err());
+    }
+}
+
 mod extract_raw_id {
     use super::*;
 
@@ -130,3 +190,42 @@ mod invalid_inputs {
         assert!(extract_id("abc@123").is_err());
     }
 }
+
+mod extract_typed_ref {
+    use share_drive::url_parser::{extract_ref, DocKind, DriveRef};
+
+    #[test]
+    fn folder_url() {
+        let (target, key) =
+            extract_ref("https://drive.google.com/drive/folders/1abc123XYZ").unwrap();
+        assert_eq!(target, DriveRef::Folder("1abc123XYZ".to_string()));
+        assert_eq!(key, None);
+    }
+
+    #[test]
+    fn file_url_is_not_a_folder() {
+        let (target, _) = extract_ref("https://drive.google.com/file/d/1abc123XYZ/view").unwrap();
+        assert_eq!(target, DriveRef::File("1abc123XYZ".to_string()));
+        assert!(target.into_folder_id().is_err());
+    }
+
+    #[test]
+    fn docs_url_kind() {
+        let (target, _) =
+            extract_ref("https://docs.google.com/forms/d/1abc123XYZ/viewform").unwrap();
+        assert_eq!(
+            target,
+            DriveRef::Doc {
+                id: "1abc123XYZ".to_string(),
+                kind: DocKind::Form
+            }
+        );
+        assert_eq!(target.id(), "1abc123XYZ");
+    }
+
+    #[test]
+    fn raw_id_may_be_a_folder() {
+        let (target, _) = extract_ref("1abc123XYZ").unwrap();
+        assert_eq!(target.into_folder_id().unwrap(), "1abc123XYZ");
+    }
+}